@@ -0,0 +1,63 @@
+//! Time and expanded cases of every search mode on the big maps of `data/`, run with `cargo bench`
+//!
+//! The times of the `Rc<RefCell>` graph, replaced by the flat arrays in the commit
+//! "Replace the Rc<RefCell> graph with flat arrays indexed by row * width + column",
+//! are measured on the parent of that commit: check it out, copy `benches/path_detective.rs`
+//! and the `[[bench]]` section of `Cargo.toml` from that commit (they build `src/` with `#[path]`),
+//! then run `cargo bench` there and on the commit itself
+
+use wazelentin::{GridInfo, Heuristic, PathDetective, SearchMode};
+use std::hint::black_box;
+use std::time::Instant;
+
+const ITERATIONS: u32 = 20;
+
+fn bench_search_mode(map_name: &str, grid_info: &GridInfo, search_mode: SearchMode) {
+    let mut wazelentin = PathDetective::new(grid_info.clone(), None);
+    wazelentin.search_mode = search_mode;
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        black_box(wazelentin.find_and_transmit_path());
+    }
+    println!(
+        "{:<28} {:<28} {:>12.3?} / solve {:>6} expanded cases",
+        map_name,
+        format!("{:?}", search_mode),
+        start.elapsed() / ITERATIONS,
+        wazelentin.expanded_cases()
+    );
+}
+
+/// Open map of 256 x 256 cases with the start and the exit at opposite corners and a wall in the middle
+fn open_map() -> String {
+    let mut rows = vec![vec!['O'; 256]; 256];
+    rows[0][0] = 'S';
+    rows[255][255] = 'X';
+    for row in rows.iter_mut().take(200).skip(56) {
+        row[128] = 'E';
+    }
+    rows.iter().map(|row| row.iter().collect::<String>()).collect::<Vec<_>>().join("\n")
+}
+
+fn main() {
+    let mut maps: Vec<(String, String)> = ["map_huge", "map_huge_straightforward"]
+        .iter()
+        .map(|map_name| {
+            let grid_row_file = std::fs::read_to_string(format!("{}/data/{}", env!("CARGO_MANIFEST_DIR"), map_name)).unwrap();
+            (map_name.to_string(), grid_row_file)
+        })
+        .collect();
+    maps.push(("open 256 x 256".to_string(), open_map()));
+    for (map_name, grid_row_file) in maps {
+        let grid_info = GridInfo::new(&grid_row_file).unwrap();
+        for search_mode in [
+            SearchMode::Bfs,
+            SearchMode::BidirectionalBfs,
+            SearchMode::Dijkstra,
+            SearchMode::AStar(Heuristic::Chebyshev),
+            SearchMode::Jps,
+        ] {
+            bench_search_mode(&map_name, &grid_info, search_mode);
+        }
+    }
+}
@@ -0,0 +1,136 @@
+use crate::path_detective::PathResult;
+use crate::pathfinder::{Pathfinder, SearchGrid};
+use std::collections::BTreeSet;
+
+/// Breadth first search from the start and from every exit at the same time, layer by layer,
+/// always growing the smaller side, until both sides meet in the middle.
+/// As with the BFS every move counts as 1, the weight of the cases and the metric are ignored
+pub struct BidirectionalBfs;
+
+/// Cases reached by one side of the search
+struct SearchSide {
+    /// moves from the first cases of the side, usize::MAX when not reached
+    distances: Vec<usize>,
+    /// case it was reached from, usize::MAX for the first cases of the side
+    parents: Vec<usize>,
+    /// cases reached by the last layer
+    frontier: Vec<(usize, usize)>,
+}
+
+impl SearchSide {
+    fn new(search_grid: &SearchGrid, first_cases: Vec<(usize, usize)>) -> SearchSide {
+        let mut distances = vec![usize::MAX; search_grid.case_count()];
+        for &pos in &first_cases {
+            distances[search_grid.index(pos)] = 0;
+        }
+        SearchSide {
+            distances,
+            parents: vec![usize::MAX; search_grid.case_count()],
+            frontier: first_cases,
+        }
+    }
+
+    /// Reach the next layer, returns the case where it meets the other side, if any
+    fn grow(&mut self, search_grid: &SearchGrid, other_side: &SearchSide) -> Option<(usize, usize)> {
+        let mut next_frontier = vec![];
+        let mut meeting_pos = None;
+        for &pos in &self.frontier {
+            search_grid.count_expanded_case();
+            let index = search_grid.index(pos);
+            for connected_pos in search_grid.connected_cases(pos.0, pos.1) {
+                let connected_index = search_grid.index(connected_pos);
+                if self.distances[connected_index] != usize::MAX {
+                    continue;
+                }
+                self.distances[connected_index] = self.distances[index] + 1;
+                self.parents[connected_index] = index;
+                next_frontier.push(connected_pos);
+                // the two sides cannot have met before this layer, so the first meeting is on a shortest path
+                if meeting_pos.is_none() && other_side.distances[connected_index] != usize::MAX {
+                    meeting_pos = Some(connected_pos);
+                }
+            }
+        }
+        self.frontier = next_frontier;
+        meeting_pos
+    }
+}
+
+impl Pathfinder for BidirectionalBfs {
+    fn name(&self) -> &'static str {
+        "bidirectional"
+    }
+
+    fn follows_costs(&self) -> bool {
+        false
+    }
+
+    fn find_path(&self, search_grid: &SearchGrid, start_pos: (usize, usize), exit_positions: &BTreeSet<(usize, usize)>) -> Option<PathResult> {
+        if !search_grid.is_valid_case(start_pos.0, start_pos.1) {
+            return None;
+        }
+        if exit_positions.contains(&start_pos) {
+            return Some(search_grid.path_result(vec![start_pos]));
+        }
+        let mut start_side = SearchSide::new(search_grid, vec![start_pos]);
+        let mut exit_side = SearchSide::new(
+            search_grid,
+            exit_positions.iter().copied().filter(|exit_pos| search_grid.is_valid_case(exit_pos.0, exit_pos.1)).collect(),
+        );
+        while !start_side.frontier.is_empty() && !exit_side.frontier.is_empty() {
+            let meeting_pos = if start_side.frontier.len() <= exit_side.frontier.len() {
+                start_side.grow(search_grid, &exit_side)
+            } else {
+                exit_side.grow(search_grid, &start_side)
+            };
+            if let Some(meeting_pos) = meeting_pos {
+                let mut path = search_grid.rebuild_path(&start_side.parents, meeting_pos);
+                let mut path_to_exit = search_grid.rebuild_path(&exit_side.parents, meeting_pos);
+                path_to_exit.reverse();
+                path.extend(path_to_exit.into_iter().skip(1));
+                return Some(search_grid.path_result(path));
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::path_detective::Metric;
+    use crate::pathfinder::tests::solve;
+    use crate::pathfinder::Bfs;
+
+    #[test]
+    fn test_bidirectional_path_goes_from_the_start_to_the_exit() {
+        let path_result = solve("OOOOOX\nOEEEEE\nSOOOOO", &BidirectionalBfs, Metric::Chebyshev).0.unwrap();
+        assert_eq!(vec![(0, 0), (1, 0), (2, 1), (2, 2), (2, 3), (2, 4), (2, 5)], path_result.path);
+        assert_eq!((2, 5), path_result.exit_pos);
+        assert_eq!(6.0, path_result.length);
+    }
+
+    #[test]
+    fn test_bidirectional_reaches_the_nearest_exit() {
+        let (path_result, _) = solve("XOOOOOOS\nOOOOOOOO\nOOOOOOOX", &BidirectionalBfs, Metric::Chebyshev);
+        assert_eq!((0, 7), path_result.unwrap().exit_pos);
+    }
+
+    #[test]
+    fn test_bidirectional_expands_fewer_cases_than_bfs() {
+        // open map of 61 x 61 cases, the exit 20 cases to the right of the start
+        let mut rows = vec![vec!['O'; 61]; 61];
+        rows[30][20] = 'S';
+        rows[30][40] = 'X';
+        let rows = rows.iter().map(|row| row.iter().collect::<String>()).collect::<Vec<_>>().join("\n");
+        let (bidirectional_result, bidirectional_expanded) = solve(&rows, &BidirectionalBfs, Metric::Chebyshev);
+        let (bfs_result, bfs_expanded) = solve(&rows, &Bfs, Metric::Chebyshev);
+        assert_eq!(bfs_result.unwrap().length, bidirectional_result.unwrap().length);
+        assert!(bidirectional_expanded * 3 < bfs_expanded * 2, "{} {}", bidirectional_expanded, bfs_expanded);
+    }
+
+    #[test]
+    fn test_bidirectional_should_return_none_if_the_sides_cannot_meet() {
+        assert!(solve("SOEOX\nOOEOO", &BidirectionalBfs, Metric::Chebyshev).0.is_none());
+    }
+}
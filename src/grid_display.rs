@@ -4,6 +4,7 @@ use std::sync::mpsc;
 use std::time::Duration;
 use termion::{clear, color, cursor};
 
+/// Terminal drawing of a grid, then of every case received until the sender is dropped
 pub struct DisplayGrid {
     pub grid_info: GridInfo,
     pub receiver: Option<mpsc::Receiver<(usize, usize)>>,
@@ -15,54 +16,52 @@ impl DisplayGrid {
         }
         print!("{}", clear::All);
         let mut x = 1;
-        let mut y = 1;
-        for lines in self.grid_info.grid.iter().rev() {
+        for (y, lines) in (1..).zip(self.grid_info.grid.iter().rev()) {
             for case in lines {
                 if case == &GridInfo::CASE_WIN {
-                    print!("{}{}A", cursor::Goto(x, y), color::Fg(color::Green));
+                    print!("{}{}{}", cursor::Goto(x, y), color::Fg(color::Green), self.grid_info.legend.symbol(*case));
                 } else if case == &GridInfo::CASE_CLOSE {
-                    print!("{}{}{}", cursor::Goto(x, y), color::Fg(color::Red), case);
+                    print!("{}{}{}", cursor::Goto(x, y), color::Fg(color::Red), self.grid_info.legend.symbol(*case));
                 } else {
                     print!(
                         "{}{}{}",
                         cursor::Goto(x, y),
                         color::Fg(color::LightBlue),
-                        case
+                        self.grid_info.legend.symbol(*case)
                     );
                 }
                 std::io::stdout().flush().unwrap();
                 x += 1;
             }
             x = 1;
-            y += 1;
         }
-        let start_x = (self.grid_info.start_pos.1 as u16) + 1;
-        let start_y = (self.grid_info.row_max as u16) - (self.grid_info.start_pos.0 as u16) + 1;
-        print!(
-            "{}{}B",
-            cursor::Goto(start_x, start_y),
-            color::Fg(color::Green)
-        );
+        for start_pos in &self.grid_info.start_positions {
+            let start_x = (start_pos.1 as u16) + 1;
+            let start_y = (self.grid_info.row_max as u16) - (start_pos.0 as u16) + 1;
+            print!(
+                "{}{}{}",
+                cursor::Goto(start_x, start_y),
+                color::Fg(color::Green),
+                self.grid_info.legend.symbol(GridInfo::CASE_START)
+            );
+        }
         print!("\n\n\n{}", cursor::Save);
 
         loop {
             if let Some(receiver) = &self.receiver {
-                if let Ok(case) = receiver.recv() {
-                    if case == (usize::MAX, usize::MAX)
-                    {
-                        return;
-                    }
-                    
+                let case = receiver.recv().unwrap_or((usize::MAX, usize::MAX));
+                // every path has been received
+                if case == (usize::MAX, usize::MAX) {
+                    print!("{}", cursor::Restore);
+                    print!("{}", color::Fg(color::Reset));
+                    print!("{}", cursor::Show);
+                    std::io::stdout().flush().unwrap();
+                    break;
+                }
+                // keep the symbol of the starts
+                if !self.grid_info.start_positions.contains(&case) {
                     let new_x = case.1 as u16 + 1;
                     let mut new_y = case.0 as u16;
-
-                    if case == self.grid_info.start_pos {
-                        print!("{}", cursor::Restore);
-                        print!("{}", color::Fg(color::Reset));
-                        print!("{}", cursor::Show);
-                        std::io::stdout().flush().unwrap();
-                        break;
-                    }
                     new_y = (self.grid_info.row_max as u16) - new_y + 1;
                     print!(
                         "{}{}{}@",
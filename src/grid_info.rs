@@ -1,117 +1,286 @@
+use std::collections::BTreeSet;
+use std::fmt;
+use std::iter::Peekable;
 use std::vec;
 
-use grid::Error;
+use crate::legend::Legend;
+use grid::{Error, ErrorKind};
 
-#[derive(Clone)]
+/// Grid of a map file with its starts and exits, rows counted from the bottom (`grid[0]`)
+#[derive(Clone, Debug)]
 pub struct GridInfo {
-    pub start_pos: (usize, usize),
-    pub exit_pos: (usize, usize),
+    /// every start of the grid, one per agent, in the order of the header
+    pub start_positions: Vec<(usize, usize)>,
+    /// every exit of the grid, a grid can have as many exits as needed
+    pub exit_positions: BTreeSet<(usize, usize)>,
     pub row_max: usize,
     pub column_max: usize,
     pub grid: Vec<Vec<char>>,
+    /// `key: value` lines of the header (name, author, seed, difficulty...), in the order of the file
+    pub metadata: Vec<(String, String)>,
+    /// characters of the map file, the grid itself always holds the cases of `GridInfo`
+    pub legend: Legend,
 }
-mod grid {
-    #[derive(PartialEq, Debug)]
-    pub enum Error {
+pub mod grid {
+    use std::fmt;
+
+    #[derive(PartialEq, Debug, Clone, Copy)]
+    pub enum ErrorKind {
         WrongOrMissingRowColumnMax = 0,
         CastForRowColumnMax,
         WrongOrMissingStartPos,
         CastForStartPos,
         IncoherenceBetweenStartPosAndMaxBound,
         MisingExit,
-        TooManyExit,
         IncoherenceGridSize,
         UnothorizedValue,
         StartPosOnBlockedCase,
+        WrongMetadata,
+        WrongLegend,
+    }
+
+    impl ErrorKind {
+        pub fn message(&self) -> &'static str {
+            match self {
+                ErrorKind::WrongOrMissingRowColumnMax => "wrong or missing `row_max column_max` line",
+                ErrorKind::CastForRowColumnMax => "row_max and column_max must be positive integers",
+                ErrorKind::WrongOrMissingStartPos => "wrong or missing `row column` start position line",
+                ErrorKind::CastForStartPos => "the start position must be positive integers",
+                ErrorKind::IncoherenceBetweenStartPosAndMaxBound => "start position out of the grid",
+                ErrorKind::MisingExit => "the grid has no exit",
+                ErrorKind::IncoherenceGridSize => "the grid size does not match `row_max column_max`",
+                ErrorKind::UnothorizedValue => "unauthorized value in the grid",
+                ErrorKind::StartPosOnBlockedCase => "start position on a blocked case",
+                ErrorKind::WrongMetadata => "wrong metadata line",
+                ErrorKind::WrongLegend => "wrong legend entry",
+            }
+        }
+    }
+
+    /// Problem found in a map file, with its position in the file
+    #[derive(PartialEq, Debug, Clone)]
+    pub struct Error {
+        pub kind: ErrorKind,
+        /// line of the map file, from 1
+        pub line: usize,
+        /// column of the line, from 1
+        pub column: usize,
+        pub found: String,
+        pub expected: String,
+    }
+
+    impl Error {
+        pub fn new(kind: ErrorKind, line: usize, column: usize, found: String, expected: String) -> Error {
+            Error {
+                kind,
+                line,
+                column,
+                found,
+                expected,
+            }
+        }
+
+        /// Compiler-style report pointing at the bad character of the map file
+        pub fn diagnostic(&self, file_name: &str, grid_row: &str) -> String {
+            let source_line = grid_row.lines().nth(self.line.saturating_sub(1)).unwrap_or("");
+            let gutter = " ".repeat(self.line.to_string().len());
+            format!(
+                "error: {}\n{}--> {}:{}:{}\n{} |\n{} | {}\n{} | {}^ found {}, expected {}",
+                self.kind.message(),
+                gutter,
+                file_name,
+                self.line,
+                self.column,
+                gutter,
+                self.line,
+                source_line,
+                gutter,
+                " ".repeat(self.column.saturating_sub(1)),
+                self.found,
+                self.expected
+            )
+        }
+    }
+
+    impl fmt::Display for Error {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write!(
+                f,
+                "line {}, column {}: {}, found {}, expected {}",
+                self.line,
+                self.column,
+                self.kind.message(),
+                self.found,
+                self.expected
+            )
+        }
     }
+
+    impl std::error::Error for Error {}
 }
 impl GridInfo {
     pub const CASE_OPEN: char = 'O';
     pub const CASE_CLOSE: char = 'E';
     pub const CASE_WIN: char = 'X';
+    /// start of a map without header, read as an open case
+    pub const CASE_START: char = 'S';
+    /// weighted cases go from '1' to '9', the digit is the cost to step on it
+    pub const CASE_COST_MIN: char = '1';
+    pub const CASE_COST_MAX: char = '9';
+    /// a line starting with it is ignored, anywhere in the map file, unless the legend makes it a case
+    pub const COMMENT: char = '#';
+    pub const METADATA_SEPARATOR: char = ':';
+    /// line between two maps of a stream
+    pub const MAP_DELIMITER: &'static str = "---";
 
-    fn check_integrity(grid_row: &str) -> Result<(), Error> {
-        let mut grid_lines = grid_row.lines();
-        if let Some(row_and_column_max_line) = grid_lines.next() {
-            let field_iterator: Vec<_> = row_and_column_max_line.split(' ').collect();
-            if field_iterator.len() == 2 {
-                if field_iterator[0].parse::<usize>().is_err() {
-                    return Err(Error::CastForRowColumnMax);
-                }
-                if field_iterator[1].parse::<usize>().is_err() {
-                    return Err(Error::CastForRowColumnMax);
-                }
-            } else {
-                return Err(Error::WrongOrMissingRowColumnMax);
-            }
+    /// Cost to step on a case, None for a blocked or unknown case
+    pub fn case_cost(case: char) -> Option<usize> {
+        match case {
+            GridInfo::CASE_OPEN | GridInfo::CASE_WIN => Some(1),
+            GridInfo::CASE_COST_MIN..=GridInfo::CASE_COST_MAX => case.to_digit(10).map(|cost| cost as usize),
+            _ => None,
         }
-        if let Some(start_pos) = grid_lines.next() {
-            let field_iterator: Vec<_> = start_pos.split(' ').collect();
-            if field_iterator.len() == 2 {
-                if field_iterator[0].parse::<usize>().is_err() {
-                    return Err(Error::CastForStartPos);
-                }
-                if field_iterator[1].parse::<usize>().is_err() {
-                    return Err(Error::CastForStartPos);
-                }
-            } else {
-                return Err(Error::WrongOrMissingStartPos);
+    }
+
+    /// Fields of a header line separated by a space, with the column (from 1) where each one starts
+    fn fields_with_column(line: &str) -> Vec<(usize, &str)> {
+        let mut column = 1;
+        line.split(' ')
+            .map(|field| {
+                let field_with_column = (column, field);
+                column += field.chars().count() + 1;
+                field_with_column
+            })
+            .collect()
+    }
+
+    fn check_row_column_max_line(row_and_column_max_line: &str, line: usize) -> Result<(), Error> {
+        let fields = GridInfo::fields_with_column(row_and_column_max_line);
+        if fields.len() != 2 {
+            return Err(Error::new(
+                ErrorKind::WrongOrMissingRowColumnMax,
+                line,
+                1,
+                format!("\"{}\"", row_and_column_max_line),
+                "`row_max column_max`".to_string(),
+            ));
+        }
+        for (column, field) in fields {
+            // the grid holds max + 1 rows and columns, which must be countable
+            if field.parse::<usize>().ok().and_then(|max| max.checked_add(1)).is_none() {
+                return Err(Error::new(
+                    ErrorKind::CastForRowColumnMax,
+                    line,
+                    column,
+                    format!("\"{}\"", field),
+                    format!("a positive integer below {}", usize::MAX),
+                ));
             }
         }
         Ok(())
     }
 
-    fn check_logic(&self, lines: std::str::Lines) -> Result<(), Error> {
-        if self.start_pos.0 > self.row_max || self.column_max < self.start_pos.1  {
-            return Err(Error::IncoherenceBetweenStartPosAndMaxBound);
+    fn check_start_pos_line(start_pos: &str, line: usize) -> Result<(), Error> {
+        let fields = GridInfo::fields_with_column(start_pos);
+        if fields.len() != 2 {
+            return Err(Error::new(
+                ErrorKind::WrongOrMissingStartPos,
+                line,
+                1,
+                format!("\"{}\"", start_pos),
+                "`row column`".to_string(),
+            ));
         }
-        let mut row: usize = self.row_max;
+        for (column, field) in fields {
+            if field.parse::<usize>().is_err() {
+                return Err(Error::new(
+                    ErrorKind::CastForStartPos,
+                    line,
+                    column,
+                    format!("\"{}\"", field),
+                    "a positive integer".to_string(),
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// `grid_lines` holds every grid line with its line number (from 1) in the map file,
+    /// the bounds are only checked when the `row_max column_max` line could be read
+    fn check_logic(&self, grid_lines: &[(usize, String)], end_line: usize, bounds_known: bool, errors: &mut Vec<Error>) {
+        // without a header, every row must be as wide as the first one
+        let mut expected_width = bounds_known.then(|| self.column_max + 1);
         let mut count_grid_line = 0;
-        #[allow(unused_assignments)]
-        let mut column: usize = 0;
         let mut nb_exit = 0;
-        for line in lines {
-            column = 0;
-            for c in line.chars() {
-                println!("{:?}{}", (row, column), c);
-               if ((row, column) == self.start_pos) && c != GridInfo::CASE_OPEN {
-                        return Err(Error::StartPosOnBlockedCase);
-                    }
-                if c != GridInfo::CASE_WIN && c != GridInfo::CASE_OPEN && c != GridInfo::CASE_CLOSE
-                {
-                    return Err(Error::UnothorizedValue);
+        for (line_index, (line_number, line)) in grid_lines.iter().enumerate() {
+            let line_number = *line_number;
+            let row = if bounds_known { self.row_max.checked_sub(line_index) } else { None };
+            for (column, c) in line.chars().enumerate() {
+                let is_start = row.is_some_and(|row| self.start_positions.contains(&(row, column)));
+                if is_start && (c == GridInfo::CASE_WIN || GridInfo::case_cost(c).is_none()) {
+                    errors.push(Error::new(
+                        ErrorKind::StartPosOnBlockedCase,
+                        line_number,
+                        column + 1,
+                        format!("'{}'", self.legend.symbol(c)),
+                        "an open or weighted case".to_string(),
+                    ));
+                } else if c != GridInfo::CASE_CLOSE && GridInfo::case_cost(c).is_none() {
+                    errors.push(Error::new(
+                        ErrorKind::UnothorizedValue,
+                        line_number,
+                        column + 1,
+                        format!("'{}'", self.legend.symbol(c)),
+                        self.legend.expected_cases(),
+                    ));
                 }
-                column += 1;
             }
             nb_exit += line.chars().filter(|&c| c == GridInfo::CASE_WIN).count();
-            if (line.len() - 1) != self.column_max
-            // start by 0 even in the grid file
-            {
-                return Err(Error::IncoherenceGridSize);
+            let width = line.chars().count();
+            match expected_width {
+                // start by 0 even in the grid file
+                Some(expected) if width != expected => errors.push(Error::new(
+                    ErrorKind::IncoherenceGridSize,
+                    line_number,
+                    width.min(expected) + 1,
+                    format!("{} cases", width),
+                    format!("{} cases", expected),
+                )),
+                Some(_) => {}
+                None => expected_width = Some(width),
             }
             count_grid_line += 1;
-            row = row.saturating_sub(1);
         }
-        if (count_grid_line - 1) != self.row_max {
-            return Err(Error::IncoherenceGridSize);
+        if bounds_known && count_grid_line != self.row_max + 1 {
+            errors.push(Error::new(
+                ErrorKind::IncoherenceGridSize,
+                grid_lines.get(self.row_max + 1).map_or(end_line, |(line_number, _)| *line_number),
+                1,
+                format!("{} rows", count_grid_line),
+                format!("{} rows", self.row_max + 1),
+            ));
         }
         if nb_exit == 0 {
-            return Err(Error::MisingExit);
-        } else if nb_exit > 1 {
-            return Err(Error::TooManyExit);
+            errors.push(Error::new(
+                ErrorKind::MisingExit,
+                grid_lines.first().map_or(end_line, |(line_number, _)| *line_number),
+                1,
+                format!("no '{}' case", self.legend.symbol(GridInfo::CASE_WIN)),
+                format!("at least one '{}' case", self.legend.symbol(GridInfo::CASE_WIN)),
+            ));
         }
-        Ok(())
     }
 
-    fn fill_grid(&mut self, grid_lines: &mut std::str::Lines) {
+    fn fill_grid(&mut self, grid_lines: &[(usize, String)]) {
         self.grid.clear();
+        self.exit_positions.clear();
         let mut current_pos = (self.row_max, 0);
-        for line in grid_lines {
+        for (_, line) in grid_lines {
             let mut grid_line: Vec<char> = vec![];
             for case in line.chars() {
                 grid_line.push(case);
                 if case == GridInfo::CASE_WIN {
-                    self.exit_pos = current_pos;
+                    self.exit_positions.insert(current_pos);
                 }
                 current_pos.1 += 1;
             }
@@ -121,40 +290,287 @@ impl GridInfo {
         }
     }
 
-    pub fn new(grid_row: &str) -> Result<GridInfo, Error> {
+    fn parse_start_pos(start_pos: &str) -> (usize, usize) {
+        let field_iterator: Vec<_> = start_pos.split(' ').collect();
+        let mut parsed_start_pos = (usize::MAX, usize::MAX);
+        if let Ok(start_row) = field_iterator[0].parse::<usize>() {
+            parsed_start_pos.0 = start_row;
+        }
+        if let Ok(start_column) = field_iterator[1].parse::<usize>() {
+            parsed_start_pos.1 = start_column;
+        }
+        parsed_start_pos
+    }
+
+    /// Read a `row column` start line, the start is kept even when out of the grid
+    fn read_start_pos_line(&mut self, start_pos: &str, line: usize, bounds_known: bool, errors: &mut Vec<Error>) {
+        if let Err(error) = GridInfo::check_start_pos_line(start_pos, line) {
+            errors.push(error);
+            return;
+        }
+        let parsed_start_pos = GridInfo::parse_start_pos(start_pos);
+        if bounds_known && (parsed_start_pos.0 > self.row_max || self.column_max < parsed_start_pos.1) {
+            errors.push(Error::new(
+                ErrorKind::IncoherenceBetweenStartPosAndMaxBound,
+                line,
+                1,
+                format!("\"{}\"", start_pos),
+                format!("a row up to {} and a column up to {}", self.row_max, self.column_max),
+            ));
+        }
+        self.start_positions.push(parsed_start_pos);
+    }
+
+    /// Read a `key: value` metadata line, None when the line is not a metadata line
+    fn read_metadata_line(line: &str, line_number: usize) -> Option<Result<(String, String), Error>> {
+        let (key, value) = line.split_once(GridInfo::METADATA_SEPARATOR)?;
+        if key.is_empty() || key.contains(char::is_whitespace) {
+            return Some(Err(Error::new(
+                ErrorKind::WrongMetadata,
+                line_number,
+                1,
+                format!("\"{}\"", line),
+                "`key: value`".to_string(),
+            )));
+        }
+        Some(Ok((key.to_string(), value.trim().to_string())))
+    }
+
+    /// Next line of the header, the metadata lines met on the way are kept on the grid
+    fn next_header_line<'a>(
+        &mut self,
+        lines: &mut Peekable<impl Iterator<Item = (usize, &'a str)>>,
+        errors: &mut Vec<Error>,
+    ) -> Option<(usize, &'a str)> {
+        while let Some(&(line_number, line)) = lines.peek() {
+            if !self.is_comment(line) {
+                match GridInfo::read_metadata_line(line, line_number) {
+                    Some(Ok(metadata)) => {
+                        if metadata.0 == Legend::KEY {
+                            self.read_legend(&metadata.1, line, line_number, errors);
+                        }
+                        self.metadata.push(metadata);
+                    }
+                    Some(Err(error)) => errors.push(error),
+                    None => return lines.next(),
+                }
+            }
+            lines.next();
+        }
+        None
+    }
+
+    /// A '#' line is a comment, unless the legend makes '#' a case of the grid
+    fn is_comment(&self, line: &str) -> bool {
+        line.starts_with(GridInfo::COMMENT) && !self.legend.has_symbol(GridInfo::COMMENT)
+    }
+
+    /// The grid lines following the legend are read through it
+    fn read_legend(&mut self, entries: &str, line: &str, line_number: usize, errors: &mut Vec<Error>) {
+        match Legend::new(entries) {
+            Ok(legend) => self.legend = legend,
+            Err(entry) => errors.push(Error::new(
+                ErrorKind::WrongLegend,
+                line_number,
+                GridInfo::fields_with_column(line)
+                    .into_iter()
+                    .find(|(_, field)| *field == entry)
+                    .map_or(1, |(column, _)| column),
+                format!("\"{}\"", entry),
+                Legend::EXPECTED_ENTRY.to_string(),
+            )),
+        }
+    }
+
+    /// Grid lines without the comments, with every map file character turned into its case
+    fn read_cases<'a>(&self, grid_lines: impl Iterator<Item = (usize, &'a str)>) -> Vec<(usize, String)> {
+        grid_lines
+            .filter(|(_, line)| !self.is_comment(line))
+            .map(|(line_number, line)| (line_number, line.chars().map(|symbol| self.legend.case(symbol)).collect()))
+            .collect()
+    }
+
+    /// A header line holds only digits and spaces and is followed by a `row column` start line,
+    /// a grid line never holds a space
+    fn is_header_line(line: &str, next_line: Option<&str>) -> bool {
+        line.contains(' ') || (line.chars().all(|c| c.is_ascii_digit()) && next_line.is_some_and(|next_line| next_line.contains(' ')))
+    }
+
+    /// Bounds and starts of a map without header, taken from the grid itself.
+    /// Every start of the grid lines is turned into an open case
+    fn read_start_markers(&mut self, grid_lines: &mut [(usize, String)], errors: &mut Vec<Error>) {
+        self.row_max = grid_lines.len() - 1;
+        self.column_max = grid_lines[0].1.chars().count().saturating_sub(1);
+        for (line_index, (_, line)) in grid_lines.iter_mut().enumerate() {
+            for (column, c) in line.chars().enumerate() {
+                if c == GridInfo::CASE_START {
+                    self.start_positions.push((self.row_max - line_index, column));
+                }
+            }
+            *line = line
+                .chars()
+                .map(|c| if c == GridInfo::CASE_START { GridInfo::CASE_OPEN } else { c })
+                .collect();
+        }
+        if self.start_positions.is_empty() {
+            errors.push(Error::new(
+                ErrorKind::WrongOrMissingStartPos,
+                grid_lines[0].0,
+                1,
+                format!("no '{}' case", self.legend.symbol(GridInfo::CASE_START)),
+                format!("at least one '{}' case", self.legend.symbol(GridInfo::CASE_START)),
+            ));
+        }
+    }
+
+    /// Read the whole map file, going on after a problem so that every error is found in one pass
+    fn parse(grid_row: &str) -> (GridInfo, Vec<Error>) {
         let mut grid_info = GridInfo {
-            start_pos: (usize::MAX, usize::MAX),
-            exit_pos: (usize::MAX, usize::MAX),
+            start_positions: vec![],
+            exit_positions: BTreeSet::new(),
             row_max: usize::MAX,
             column_max: usize::MAX,
             grid: vec![vec![]],
+            metadata: vec![],
+            legend: Legend::default(),
         };
+        let mut errors = vec![];
 
-        GridInfo::check_integrity(grid_row)?;
-        let mut grid_lines: std::str::Lines<'_> = grid_row.lines(); // why must it be mut ?
-        if let Some(row_and_column_max_line) = grid_lines.next() {
-            let field_iterator: Vec<_> = row_and_column_max_line.split(' ').collect();
-            if let Ok(row) = field_iterator[0].parse::<usize>() {
-                grid_info.row_max = row;
+        // line numbers start by 1 in the map file, comments are skipped everywhere
+        let mut lines = grid_row
+            .lines()
+            .enumerate()
+            .map(|(line_index, line)| (line_index + 1, line))
+            .peekable();
+        let end_line = grid_row.lines().count() + 1;
+        let first_line = grid_info.next_header_line(&mut lines, &mut errors);
+        let next_line = lines.clone().map(|(_, line)| line).find(|line| !grid_info.is_comment(line));
+        if let Some(first_grid_line) = first_line.filter(|&(_, line)| !GridInfo::is_header_line(line, next_line)) {
+            // no header: the bounds come from the grid and the starts are marked in it
+            let mut grid_lines = grid_info.read_cases(std::iter::once(first_grid_line).chain(lines));
+            grid_info.read_start_markers(&mut grid_lines, &mut errors);
+            grid_info.check_logic(&grid_lines, end_line, true, &mut errors);
+            if errors.is_empty() {
+                grid_info.fill_grid(&grid_lines);
             }
-            if let Ok(column) = field_iterator[1].parse::<usize>() {
-                grid_info.column_max = column;
+            return (grid_info, errors);
+        }
+        let mut bounds_known = false;
+        match first_line {
+            Some((line_number, row_and_column_max_line)) => {
+                match GridInfo::check_row_column_max_line(row_and_column_max_line, line_number) {
+                    Ok(()) => {
+                        let field_iterator: Vec<_> = row_and_column_max_line.split(' ').collect();
+                        if let Ok(row) = field_iterator[0].parse::<usize>() {
+                            grid_info.row_max = row;
+                        }
+                        if let Ok(column) = field_iterator[1].parse::<usize>() {
+                            grid_info.column_max = column;
+                        }
+                        bounds_known = true;
+                    }
+                    Err(error) => errors.push(error),
+                }
             }
+            None => errors.push(Error::new(
+                ErrorKind::WrongOrMissingRowColumnMax,
+                end_line,
+                1,
+                "the end of the file".to_string(),
+                "`row_max column_max`".to_string(),
+            )),
+        }
+
+        match grid_info.next_header_line(&mut lines, &mut errors) {
+            Some((line_number, start_pos)) => grid_info.read_start_pos_line(start_pos, line_number, bounds_known, &mut errors),
+            None => errors.push(Error::new(
+                ErrorKind::WrongOrMissingStartPos,
+                end_line,
+                1,
+                "the end of the file".to_string(),
+                "`row column`".to_string(),
+            )),
+        }
+        // a grid line never holds a space, any following line which does is another start
+        let mut grid_lines = vec![];
+        while let Some((line_number, line)) = grid_info.next_header_line(&mut lines, &mut errors) {
+            if !line.contains(' ') {
+                grid_lines.push((line_number, line));
+                break;
+            }
+            grid_info.read_start_pos_line(line, line_number, bounds_known, &mut errors);
+        }
+        let grid_lines = grid_info.read_cases(grid_lines.into_iter().chain(lines));
+
+        grid_info.check_logic(&grid_lines, end_line, bounds_known, &mut errors);
+        if errors.is_empty() {
+            grid_info.fill_grid(&grid_lines);
+        }
+        (grid_info, errors)
+    }
+
+    /// Map format:
+    /// `row_max column_max` line, then one `row column` line per start (at least one),
+    /// then the grid lines from the top row (`row_max`) to the bottom row (0).
+    /// `key: value` metadata lines can come anywhere before the grid and `#` comment lines anywhere.
+    /// The header can be left out: the bounds are then taken from the grid and every start is marked with 'S'.
+    /// A `legend: .=open #=wall @=start >=exit ~=3` metadata line gives other characters to the cases
+    pub fn new(grid_row: &str) -> Result<GridInfo, Error> {
+        let (grid_info, errors) = GridInfo::parse(grid_row);
+        match errors.into_iter().next() {
+            Some(error) => Err(error),
+            None => Ok(grid_info),
         }
+    }
+
+    /// Every problem of the map file in the order of the file, empty for a valid map
+    pub fn validate(grid_row: &str) -> Vec<Error> {
+        GridInfo::parse(grid_row).1
+    }
 
-        if let Some(start_pos) = grid_lines.next() {
-            let field_iterator: Vec<_> = start_pos.split(' ').collect();
-            if let Ok(start_row) = field_iterator[0].parse::<usize>() {
-                grid_info.start_pos.0 = start_row;
+    /// Maps of a stream separated by `---` lines, each with the number of stream lines before it
+    /// to give the errors their line in the stream. Blank parts, such as after a last `---`, are left out
+    pub fn split_maps(stream: &str) -> Vec<(usize, String)> {
+        let mut maps = vec![];
+        let mut first_line = 0;
+        let mut map_lines: Vec<&str> = vec![];
+        for (line_index, line) in stream.lines().chain(std::iter::once(GridInfo::MAP_DELIMITER)).enumerate() {
+            if line.trim_end() != GridInfo::MAP_DELIMITER {
+                map_lines.push(line);
+                continue;
             }
-            if let Ok(start_column) = field_iterator[1].parse::<usize>() {
-                grid_info.start_pos.1 = start_column;
+            if map_lines.iter().any(|map_line| !map_line.trim().is_empty()) {
+                maps.push((first_line, map_lines.join("\n")));
             }
+            map_lines.clear();
+            first_line = line_index + 1;
         }
+        maps
+    }
 
-        grid_info.check_logic(grid_lines.clone())?;
-        grid_info.fill_grid(&mut grid_lines);
-        Ok(grid_info)
+    /// Map file text of the grid, read back by `GridInfo::new` into the same grid.
+    /// The text is in a normalized form: the metadata lines come first, then `row_max column_max`,
+    /// the start lines and the grid. The comments are dropped and a map without header is given one,
+    /// so only a map file already in this form is written back exactly
+    pub fn to_map_string(&self) -> String {
+        self.to_string()
+    }
+}
+
+impl fmt::Display for GridInfo {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (key, value) in &self.metadata {
+            writeln!(f, "{}{} {}", key, GridInfo::METADATA_SEPARATOR, value)?;
+        }
+        writeln!(f, "{} {}", self.row_max, self.column_max)?;
+        for start_pos in &self.start_positions {
+            writeln!(f, "{} {}", start_pos.0, start_pos.1)?;
+        }
+        // grid[0] is the bottom row but the file starts with the top one
+        for grid_line in self.grid.iter().rev() {
+            writeln!(f, "{}", grid_line.iter().map(|&case| self.legend.symbol(case)).collect::<String>())?;
+        }
+        Ok(())
     }
 }
 #[cfg(test)]
@@ -166,8 +582,8 @@ mod tests {
         if let Ok(grid_info) = GridInfo::new(test_input_grid_row) {
             assert_eq!(2, grid_info.row_max);
             assert_eq!(6, grid_info.column_max);
-            assert_eq!((0, 2), grid_info.start_pos);
-            assert_eq!((2, 1), grid_info.exit_pos);
+            assert_eq!([(0, 2)].to_vec(), grid_info.start_positions);
+            assert_eq!(BTreeSet::from([(2, 1)]), grid_info.exit_positions);
             let grid_mocked: Vec<Vec<char>> = [
                 ['E', 'O', 'O', 'E', 'E', 'E', 'E'].to_vec(),
                 ['O', 'O', 'O', 'O', 'O', 'E', 'E'].to_vec(),
@@ -183,7 +599,7 @@ mod tests {
     fn test_should_return_error_for_start_position_if_start_sport_start_on_wrong_case() {
         let test_input_grid_row = &String::from("2 6\n0 2\nEXEEEEE\nOOOOOEE\nEOEEEEE");
         if let Err(grid_error) = GridInfo::new(test_input_grid_row) {
-            assert_eq!(Error::StartPosOnBlockedCase, grid_error);
+            assert_eq!(ErrorKind::StartPosOnBlockedCase, grid_error.kind);
         } else {
             panic!("Expected an error, but got Ok");
         }
@@ -192,7 +608,7 @@ mod tests {
     fn test_should_return_error_wrong_or_missing_for_row_with_missing_value() {
         let test_input_grid_row = &String::from("6\n0 2\nOXOEEEE\nOOOOOEE\nEOOEEEE");
         if let Err(grid_error) = GridInfo::new(test_input_grid_row) {
-            assert_eq!(Error::WrongOrMissingRowColumnMax, grid_error);
+            assert_eq!(ErrorKind::WrongOrMissingRowColumnMax, grid_error.kind);
         } else {
             panic!("Expected an error, but got Ok");
         }
@@ -201,7 +617,7 @@ mod tests {
     fn test_should_return_error_wrong_or_missing_for_row_with_addidional_value() {
         let test_input_grid_row = &String::from("6 6 6\n0 2\nOXOEEEE\nOOOOOEE\nEOOEEEE");
         if let Err(grid_error) = GridInfo::new(test_input_grid_row) {
-            assert_eq!(Error::WrongOrMissingRowColumnMax, grid_error);
+            assert_eq!(ErrorKind::WrongOrMissingRowColumnMax, grid_error.kind);
         } else {
             panic!("Expected an error, but got Ok");
         }
@@ -210,7 +626,7 @@ mod tests {
     fn test_should_return_error_cast_for_row_with_incorrect_value() {
         let test_input_grid_row = &String::from("6 r\n0 2\nOXOEEEE\nOOOOOEE\nEOOEEEE");
         if let Err(grid_error) = GridInfo::new(test_input_grid_row) {
-            assert_eq!(Error::CastForRowColumnMax, grid_error);
+            assert_eq!(ErrorKind::CastForRowColumnMax, grid_error.kind);
         } else {
             panic!("Expected an error, but got Ok");
         }
@@ -219,7 +635,7 @@ mod tests {
     fn test_should_return_error_wrong_or_missing_for_start_pos_with_missing_value() {
         let test_input_grid_row = &String::from("6 2\n2\nOXOEEEE\nOOOOOEE\nEOOEEEE");
         if let Err(grid_error) = GridInfo::new(test_input_grid_row) {
-            assert_eq!(Error::WrongOrMissingStartPos, grid_error);
+            assert_eq!(ErrorKind::WrongOrMissingStartPos, grid_error.kind);
         } else {
             panic!("Expected an error, but got Ok");
         }
@@ -228,7 +644,7 @@ mod tests {
     fn test_should_return_error_wrong_or_missing_for_start_pos_with_additional_value() {
         let test_input_grid_row = &String::from("6 2\n2 2 2\nOXOEEEE\nOOOOOEE\nEOOEEEE");
         if let Err(grid_error) = GridInfo::new(test_input_grid_row) {
-            assert_eq!(Error::WrongOrMissingStartPos, grid_error);
+            assert_eq!(ErrorKind::WrongOrMissingStartPos, grid_error.kind);
         } else {
             panic!("Expected an error, but got Ok");
         }
@@ -237,7 +653,7 @@ mod tests {
     fn test_should_return_error_cast_for_start_pos_with_incorrect_value() {
         let test_input_grid_row = &String::from("6 2\n0 p\nOXOEEEE\nOOOOOEE\nEOOEEEE");
         if let Err(grid_error) = GridInfo::new(test_input_grid_row) {
-            assert_eq!(Error::CastForStartPos, grid_error);
+            assert_eq!(ErrorKind::CastForStartPos, grid_error.kind);
         } else {
             panic!("Expected an error, but got Ok");
         }
@@ -246,7 +662,7 @@ mod tests {
     fn test_should_return_error_cast_for_doing_shit() {
         let test_input_grid_row = &String::from("6 22OXOEEEE\nOOOOOEE\nEOOEEEE");
         if let Err(grid_error) = GridInfo::new(test_input_grid_row) {
-            assert_eq!(Error::CastForRowColumnMax, grid_error);
+            assert_eq!(ErrorKind::CastForRowColumnMax, grid_error.kind);
         } else {
             panic!("Expected an error, but got Ok");
         }
@@ -255,7 +671,7 @@ mod tests {
     fn test_should_return_error_cast_for_doing_shit_again() {
         let test_input_grid_row = &String::from("6 2\n2O XOEEEE\nOOOOOEE\nEOOEEEE");
         if let Err(grid_error) = GridInfo::new(test_input_grid_row) {
-            assert_eq!(Error::CastForStartPos, grid_error);
+            assert_eq!(ErrorKind::CastForStartPos, grid_error.kind);
         } else {
             panic!("Expected an error, but got Ok");
         }
@@ -264,7 +680,7 @@ mod tests {
     fn test_should_return_incoherence_error_for_start_pos_superior_to_row_or_column() {
         let test_input_grid_row = &String::from("6 2\n6 3\nXOEEEE\nOOOOOEE\nEOOEEEE");
         if let Err(grid_error) = GridInfo::new(test_input_grid_row) {
-            assert_eq!(Error::IncoherenceBetweenStartPosAndMaxBound, grid_error);
+            assert_eq!(ErrorKind::IncoherenceBetweenStartPosAndMaxBound, grid_error.kind);
         } else {
             panic!("Expected an error, but got Ok");
         }
@@ -273,7 +689,7 @@ mod tests {
     fn test_should_return_incoherence_error_for_grid_which_does_not_respect_bound() {
         let test_input_grid_row = &String::from("6 2\n6 2\nXOOEEE\nOOOEE\nEOOEEEE");
         if let Err(grid_error) = GridInfo::new(test_input_grid_row) {
-            assert_eq!(Error::IncoherenceGridSize, grid_error);
+            assert_eq!(ErrorKind::IncoherenceGridSize, grid_error.kind);
         } else {
             panic!("Expected an error, but got Ok");
         }
@@ -282,7 +698,7 @@ mod tests {
     fn test_should_return_incoherence_error_for_grid_which_does_not_respect_bound_again() {
         let test_input_grid_row = &String::from("2 6\n0 2\nOXOEEEE\nOOOOOEE\nEOOEEEE\nE\n");
         if let Err(grid_error) = GridInfo::new(test_input_grid_row) {
-            assert_eq!(Error::IncoherenceGridSize, grid_error);
+            assert_eq!(ErrorKind::IncoherenceGridSize, grid_error.kind);
         } else {
             panic!("Expected an error, but got Ok");
         }
@@ -291,27 +707,337 @@ mod tests {
     fn test_should_return_missing_exit_error_for_grid_which_does_not_have_exit() {
         let test_input_grid_row = &String::from("2 6\n0 2\nOOOEEEE\nOOOOOEE\nEOOEEEE");
         if let Err(grid_error) = GridInfo::new(test_input_grid_row) {
-            assert_eq!(Error::MisingExit, grid_error);
+            assert_eq!(ErrorKind::MisingExit, grid_error.kind);
+        } else {
+            panic!("Expected an error, but got Ok");
+        }
+    }
+    #[test]
+    fn test_should_return_every_exit_for_grid_which_have_more_than_one_exit() {
+        let test_input_grid_row = &String::from("2 6\n0 2\nXOOEEEE\nOOXXOEE\nEOOEEEE");
+        if let Ok(grid_info) = GridInfo::new(test_input_grid_row) {
+            assert_eq!(BTreeSet::from([(1, 2), (1, 3), (2, 0)]), grid_info.exit_positions);
+        } else {
+            panic!("Expected OK, but got an Error");
+        }
+    }
+    #[test]
+    fn test_should_return_every_start_position() {
+        let test_input_grid_row = &String::from("2 6\n0 2\n1 4\n2 0\nOXOEEEE\nOOOOOEE\nEOOEEEE");
+        if let Ok(grid_info) = GridInfo::new(test_input_grid_row) {
+            assert_eq!([(0, 2), (1, 4), (2, 0)].to_vec(), grid_info.start_positions);
+            assert_eq!(3, grid_info.grid.len());
+        } else {
+            panic!("Expected OK, but got an Error");
+        }
+    }
+    #[test]
+    fn test_should_return_error_cast_for_additional_start_pos_with_incorrect_value() {
+        let test_input_grid_row = &String::from("2 6\n0 2\n1 x\nOXOEEEE\nOOOOOEE\nEOOEEEE");
+        if let Err(grid_error) = GridInfo::new(test_input_grid_row) {
+            assert_eq!(ErrorKind::CastForStartPos, grid_error.kind);
+        } else {
+            panic!("Expected an error, but got Ok");
+        }
+    }
+    #[test]
+    fn test_should_return_error_for_additional_start_pos_on_wrong_case() {
+        let test_input_grid_row = &String::from("2 6\n0 2\n1 6\nOXOEEEE\nOOOOOEE\nEOOEEEE");
+        if let Err(grid_error) = GridInfo::new(test_input_grid_row) {
+            assert_eq!(ErrorKind::StartPosOnBlockedCase, grid_error.kind);
         } else {
             panic!("Expected an error, but got Ok");
         }
     }
     #[test]
-    fn test_should_return_multiple_exist_for_grid_which_have_more_than_one_exit() {
-        let test_input_grid_row = &String::from("2 6\n0 2\nOOOEEEE\nOOXXOEE\nEOOEEEE");
+    fn test_should_accept_weighted_cases() {
+        let test_input_grid_row = &String::from("2 6\n0 2\nOX9EEEE\nO1O2OEE\nEO5EEEE");
+        if let Ok(grid_info) = GridInfo::new(test_input_grid_row) {
+            assert_eq!([(0, 2)].to_vec(), grid_info.start_positions);
+            assert_eq!(['O', '1', 'O', '2', 'O', 'E', 'E'].to_vec(), grid_info.grid[1]);
+        } else {
+            panic!("Expected OK, but got an Error");
+        }
+    }
+    #[test]
+    fn test_should_return_error_if_zero_is_used_as_a_weighted_case() {
+        let test_input_grid_row = &String::from("2 6\n0 2\nOX0EEEE\nOOOOOEE\nEOOEEEE");
         if let Err(grid_error) = GridInfo::new(test_input_grid_row) {
-            assert_eq!(Error::TooManyExit, grid_error);
+            assert_eq!(ErrorKind::UnothorizedValue, grid_error.kind);
         } else {
             panic!("Expected an error, but got Ok");
         }
     }
     #[test]
+    fn test_case_cost() {
+        assert_eq!(Some(1), GridInfo::case_cost(GridInfo::CASE_OPEN));
+        assert_eq!(Some(1), GridInfo::case_cost(GridInfo::CASE_WIN));
+        assert_eq!(Some(7), GridInfo::case_cost('7'));
+        assert_eq!(None, GridInfo::case_cost(GridInfo::CASE_CLOSE));
+        assert_eq!(None, GridInfo::case_cost('0'));
+    }
+    #[test]
     fn test_should_return_error_if_an_unothorized_value_is_present() {
         let test_input_grid_row = &String::from("2 6\n0 2\nOOOAEEE\nOOOOOEE\nEOOEEXE");
         if let Err(grid_error) = GridInfo::new(test_input_grid_row) {
-            assert_eq!(Error::UnothorizedValue, grid_error);
+            assert_eq!(ErrorKind::UnothorizedValue, grid_error.kind);
         } else {
             panic!("Expected an error, but got Ok");
         }
     }
+    #[test]
+    fn test_error_should_point_at_the_unothorized_case() {
+        let test_input_grid_row = &String::from("2 6\n0 2\nOOOAEEE\nOOOOOEE\nEOOEEXE");
+        let grid_error = GridInfo::new(test_input_grid_row).err().unwrap();
+        assert_eq!(
+            Error::new(ErrorKind::UnothorizedValue, 3, 4, "'A'".to_string(), Legend::default().expected_cases()),
+            grid_error
+        );
+    }
+    #[test]
+    fn test_error_should_point_at_the_bad_field_of_an_additional_start() {
+        let test_input_grid_row = &String::from("2 6\n0 2\n1 a2\nOXOEEEE\nOOOOOEE\nEOOEEEE");
+        let grid_error = GridInfo::new(test_input_grid_row).err().unwrap();
+        assert_eq!(ErrorKind::CastForStartPos, grid_error.kind);
+        assert_eq!((3, 3), (grid_error.line, grid_error.column));
+        assert_eq!("\"a2\"", grid_error.found);
+    }
+    #[test]
+    fn test_error_should_report_the_size_of_a_ragged_row() {
+        let test_input_grid_row = &String::from("2 6\n0 2\nOXOEEEE\nOOOOOE\nEOOEEEE");
+        let grid_error = GridInfo::new(test_input_grid_row).err().unwrap();
+        assert_eq!(ErrorKind::IncoherenceGridSize, grid_error.kind);
+        assert_eq!((4, 7), (grid_error.line, grid_error.column));
+        assert_eq!(("6 cases", "7 cases"), (grid_error.found.as_str(), grid_error.expected.as_str()));
+    }
+    #[test]
+    fn test_should_return_error_instead_of_panicking_on_truncated_file() {
+        assert_eq!(ErrorKind::WrongOrMissingRowColumnMax, GridInfo::new("").err().unwrap().kind);
+        assert_eq!(ErrorKind::WrongOrMissingStartPos, GridInfo::new("2 6").err().unwrap().kind);
+        let grid_error = GridInfo::new("2 6\n0 2").err().unwrap();
+        assert_eq!(ErrorKind::IncoherenceGridSize, grid_error.kind);
+        assert_eq!(("0 rows", "3 rows"), (grid_error.found.as_str(), grid_error.expected.as_str()));
+        assert_eq!(ErrorKind::IncoherenceGridSize, GridInfo::new("2 6\n0 2\n\nOOOOOEE\nEOOEEXE").err().unwrap().kind);
+    }
+    #[test]
+    fn test_diagnostic_should_show_the_line_and_a_caret_under_the_case() {
+        let test_input_grid_row = "2 6\n0 2\nOOOAEEE\nOOOOOEE\nEOOEEXE";
+        let grid_error = GridInfo::new(test_input_grid_row).err().unwrap();
+        assert_eq!(
+            "error: unauthorized value in the grid\n --> map:3:4\n  |\n3 | OOOAEEE\n  |    ^ found 'A', expected ".to_string()
+                + &Legend::default().expected_cases(),
+            grid_error.diagnostic("map", test_input_grid_row)
+        );
+        assert_eq!(
+            "line 3, column 4: unauthorized value in the grid, found 'A', expected ".to_string() + &Legend::default().expected_cases(),
+            grid_error.to_string()
+        );
+    }
+    #[test]
+    fn test_validate_should_return_nothing_for_a_valid_map() {
+        assert_eq!(Vec::<Error>::new(), GridInfo::validate("2 6\n0 2\n1 1\nOXOEEEE\nOOOOOEE\nEOOEEEE"));
+    }
+    #[test]
+    fn test_validate_should_return_every_error_in_the_order_of_the_file() {
+        let test_input_grid_row = "2 6\n0 a\n9 1\nOOOAEEE\nOOOOOE\nEOOEE?E";
+        let errors: Vec<_> = GridInfo::validate(test_input_grid_row)
+            .iter()
+            .map(|error| (error.kind, error.line, error.column))
+            .collect();
+        assert_eq!(
+            vec![
+                (ErrorKind::CastForStartPos, 2, 3),
+                (ErrorKind::IncoherenceBetweenStartPosAndMaxBound, 3, 1),
+                (ErrorKind::UnothorizedValue, 4, 4),
+                (ErrorKind::IncoherenceGridSize, 5, 7),
+                (ErrorKind::UnothorizedValue, 6, 6),
+                (ErrorKind::MisingExit, 4, 1),
+            ],
+            errors
+        );
+    }
+    #[test]
+    fn test_validate_should_check_the_grid_even_with_a_wrong_header() {
+        let errors: Vec<_> = GridInfo::validate("2\n0 2\nOXOEEEE\nOOOOE\nEOOEEEE")
+            .iter()
+            .map(|error| (error.kind, error.line))
+            .collect();
+        // without row_max column_max the rows are compared with the first one
+        assert_eq!(vec![(ErrorKind::WrongOrMissingRowColumnMax, 1), (ErrorKind::IncoherenceGridSize, 4)], errors);
+    }
+    #[test]
+    fn test_should_return_error_cast_for_row_or_column_max_without_case_count() {
+        for test_input_grid_row in ["18446744073709551615 0\n0 0\nX", "0 18446744073709551615\n0 0\nX"] {
+            assert_eq!(ErrorKind::CastForRowColumnMax, GridInfo::new(test_input_grid_row).err().unwrap().kind);
+            let errors: Vec<_> = GridInfo::validate(test_input_grid_row).iter().map(|error| error.kind).collect();
+            assert_eq!(vec![ErrorKind::CastForRowColumnMax], errors);
+        }
+    }
+    #[test]
+    fn test_to_map_string_should_write_the_top_row_first() {
+        let test_input_grid_row = "2 6\n0 2\n1 1\nOXOEEEE\nOOOOOEE\nEOOEEEE\n";
+        let grid_info = GridInfo::new(test_input_grid_row).unwrap();
+        assert_eq!(vec!['E', 'O', 'O', 'E', 'E', 'E', 'E'], grid_info.grid[0]);
+        assert_eq!(test_input_grid_row, grid_info.to_map_string());
+    }
+    #[test]
+    fn test_to_map_string_should_normalize_comments_and_header_order() {
+        let test_input_grid_row = "# first comment\n2 6\nname: mixed\n0 2\n# between the starts\n1 1\nlegend: .=open\nOXOEEEE\n# in the grid\n.....EE\nE..EEEE\n";
+        let grid_info = GridInfo::new(test_input_grid_row).unwrap();
+        let normalized = "name: mixed\nlegend: .=open\n2 6\n0 2\n1 1\n.X.EEEE\n.....EE\nE..EEEE\n";
+        assert_eq!(normalized, grid_info.to_map_string());
+        let grid_info_read_back = GridInfo::new(normalized).unwrap();
+        assert_eq!(grid_info.grid, grid_info_read_back.grid);
+        assert_eq!(grid_info.start_positions, grid_info_read_back.start_positions);
+        assert_eq!(grid_info.metadata, grid_info_read_back.metadata);
+        assert_eq!(normalized, grid_info_read_back.to_map_string());
+    }
+    #[test]
+    fn test_every_data_map_should_round_trip() {
+        let data_dir = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("data");
+        let mut nb_map = 0;
+        for entry in std::fs::read_dir(data_dir).unwrap() {
+            let map_path = entry.unwrap().path();
+            if !map_path.is_file() {
+                continue;
+            }
+            let grid_row_file = std::fs::read_to_string(&map_path).unwrap();
+            let grid_info = GridInfo::new(&grid_row_file).unwrap();
+            assert_eq!(grid_row_file, grid_info.to_map_string(), "{:?}", map_path);
+            let grid_info_read_back = GridInfo::new(&grid_info.to_map_string()).unwrap();
+            assert_eq!(grid_info.grid, grid_info_read_back.grid, "{:?}", map_path);
+            assert_eq!(grid_info.exit_positions, grid_info_read_back.exit_positions, "{:?}", map_path);
+            nb_map += 1;
+        }
+        assert!(nb_map > 0);
+    }
+    #[test]
+    fn test_should_skip_comments_and_keep_metadata() {
+        let test_input_grid_row = "# two ways to the exit\nname: two ways\n2 6\nauthor: wazelentin\n0 2\n# second start\n1 1\ndifficulty:  easy \nOXOEEEE\n# middle row\nOOOOOEE\nEOOEEEE";
+        let grid_info = GridInfo::new(test_input_grid_row).unwrap();
+        assert_eq!(vec![(0, 2), (1, 1)], grid_info.start_positions);
+        assert_eq!(BTreeSet::from([(2, 1)]), grid_info.exit_positions);
+        assert_eq!(3, grid_info.grid.len());
+        assert_eq!(
+            vec![
+                ("name".to_string(), "two ways".to_string()),
+                ("author".to_string(), "wazelentin".to_string()),
+                ("difficulty".to_string(), "easy".to_string()),
+            ],
+            grid_info.metadata
+        );
+        assert_eq!(
+            "name: two ways\nauthor: wazelentin\ndifficulty: easy\n2 6\n0 2\n1 1\nOXOEEEE\nOOOOOEE\nEOOEEEE\n",
+            grid_info.to_map_string()
+        );
+    }
+    #[test]
+    fn test_error_line_should_count_comments_and_metadata() {
+        let test_input_grid_row = "# comment\nseed: 42\n2 6\n0 2\nOOOAEEE\n# comment\nOOOOOEE\nEOOEEXE";
+        let grid_error = GridInfo::new(test_input_grid_row).err().unwrap();
+        assert_eq!((ErrorKind::UnothorizedValue, 5, 4), (grid_error.kind, grid_error.line, grid_error.column));
+        let errors = GridInfo::validate("2 6\n0 2\nbad key: 1\nOOOOEEE\n# comment\nOOOOOEE\nEOOEEXE\nOOOOOOO");
+        let errors: Vec<_> = errors.iter().map(|error| (error.kind, error.line)).collect();
+        assert_eq!(vec![(ErrorKind::WrongMetadata, 3), (ErrorKind::IncoherenceGridSize, 8)], errors);
+    }
+    #[test]
+    fn test_should_infer_bounds_and_starts_without_header() {
+        let test_input_grid_row = "name: marked\nOXOEEEE\n# middle row\nOSOOOEE\nESOEEEE\n";
+        let grid_info = GridInfo::new(test_input_grid_row).unwrap();
+        assert_eq!((2, 6), (grid_info.row_max, grid_info.column_max));
+        assert_eq!(vec![(1, 1), (0, 1)], grid_info.start_positions);
+        assert_eq!(BTreeSet::from([(2, 1)]), grid_info.exit_positions);
+        assert_eq!(vec!['E', 'O', 'O', 'E', 'E', 'E', 'E'], grid_info.grid[0]);
+        assert_eq!(
+            "name: marked\n2 6\n1 1\n0 1\nOXOEEEE\nOOOOOEE\nEOOEEEE\n",
+            grid_info.to_map_string()
+        );
+    }
+    #[test]
+    fn test_should_read_a_weighted_top_row_without_header_as_grid() {
+        let grid_info = GridInfo::new("1234\nSOOX").unwrap();
+        assert_eq!((1, 3), (grid_info.row_max, grid_info.column_max));
+        assert_eq!(vec![(0, 0)], grid_info.start_positions);
+        assert_eq!(vec!['1', '2', '3', '4'], grid_info.grid[1]);
+    }
+    #[test]
+    fn test_should_return_errors_of_a_map_without_header() {
+        let grid_error = GridInfo::new("OXOEEEE\nOOOOOEE").err().unwrap();
+        assert_eq!((ErrorKind::WrongOrMissingStartPos, 1), (grid_error.kind, grid_error.line));
+        assert_eq!("no 'S' case", grid_error.found);
+        let errors: Vec<_> = GridInfo::validate("OXOEEEE\nOSOOOE\nESOEEXS")
+            .iter()
+            .map(|error| (error.kind, error.line, error.column))
+            .collect();
+        assert_eq!(vec![(ErrorKind::IncoherenceGridSize, 2, 7)], errors);
+        assert_eq!(ErrorKind::UnothorizedValue, GridInfo::new("2 6\n0 2\nOXOEEEE\nOOSOOEE\nEOOEEEE").err().unwrap().kind);
+    }
+    #[test]
+    fn test_every_data_map_without_header_should_be_read_back() {
+        let data_dir = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("data").join("without_header");
+        let mut nb_map = 0;
+        for entry in std::fs::read_dir(data_dir).unwrap() {
+            let map_path = entry.unwrap().path();
+            let grid_info = GridInfo::new(&std::fs::read_to_string(&map_path).unwrap()).unwrap();
+            let grid_info_read_back = GridInfo::new(&grid_info.to_map_string()).unwrap();
+            assert_eq!(grid_info.start_positions, grid_info_read_back.start_positions, "{:?}", map_path);
+            assert_eq!(grid_info.grid, grid_info_read_back.grid, "{:?}", map_path);
+            nb_map += 1;
+        }
+        assert!(nb_map > 0);
+    }
+    #[test]
+    fn test_should_read_and_write_the_grid_through_the_legend() {
+        let test_input_grid_row = "# comment before the legend\nlegend: .=open #=wall >=exit ~=3\n2 6\n0 2\n#>.####\n...~.##\n#..####\n";
+        let grid_info = GridInfo::new(test_input_grid_row).unwrap();
+        assert_eq!(vec!['E', 'O', 'O', 'E', 'E', 'E', 'E'], grid_info.grid[0]);
+        assert_eq!(vec!['O', 'O', 'O', '3', 'O', 'E', 'E'], grid_info.grid[1]);
+        assert_eq!(BTreeSet::from([(2, 1)]), grid_info.exit_positions);
+        assert_eq!(
+            "legend: .=open #=wall >=exit ~=3\n2 6\n0 2\n#>.####\n...~.##\n#..####\n",
+            grid_info.to_map_string()
+        );
+    }
+    #[test]
+    fn test_map_written_through_a_legend_should_be_read_back() {
+        // 'X' becomes a wall, so the exit must be written with its own symbol
+        let grid_info = GridInfo::new("legend: X=wall >=exit ~=3\n2 3\n0 0\nO>OX\nO~OX\nOXXX").unwrap();
+        let grid_info_read_back = GridInfo::new(&grid_info.to_map_string()).unwrap();
+        assert_eq!("legend: X=wall >=exit ~=3\n2 3\n0 0\nO>OX\nO~OX\nOXXX\n", grid_info.to_map_string());
+        assert_eq!(grid_info.grid, grid_info_read_back.grid);
+        assert_eq!(grid_info.exit_positions, grid_info_read_back.exit_positions);
+        let grid_error = GridInfo::new("legend: X=wall\n1 1\n0 0\nOO\nXO").err().unwrap();
+        assert_eq!((ErrorKind::WrongLegend, "\"X=wall\""), (grid_error.kind, grid_error.found.as_str()));
+    }
+    #[test]
+    fn test_legend_should_not_give_a_case_the_symbol_of_metadata_lines() {
+        // the first grid row would be read as a `.: X.` metadata line
+        let grid_error = GridInfo::new("legend: .=open #=wall :=3\n2 3\n0 0\n.:X.\n...#\n....").err().unwrap();
+        assert_eq!((ErrorKind::WrongLegend, 1, 23), (grid_error.kind, grid_error.line, grid_error.column));
+    }
+    #[test]
+    fn test_should_mark_starts_with_the_legend_without_header() {
+        let grid_info = GridInfo::new("legend: .=open @=start T=wall >=exit\nT>T\n.@.").unwrap_or_else(|error| panic!("{}", error));
+        assert_eq!(vec![(0, 1)], grid_info.start_positions);
+        assert_eq!(vec!['O', 'O', 'O'], grid_info.grid[0]);
+    }
+    #[test]
+    fn test_errors_should_show_the_characters_of_the_legend() {
+        let grid_error = GridInfo::new("legend: .=open #=wall\n2 6\n0 2\n#X.####\n...A.##\n#..####").err().unwrap();
+        assert_eq!((ErrorKind::UnothorizedValue, 5, 4), (grid_error.kind, grid_error.line, grid_error.column));
+        assert_eq!("one of '.', '#', 'X' or a cost from '1' to '9'", grid_error.expected);
+        let grid_error = GridInfo::new("legend: .=open #=wall\n2 6\n0 2\n#X.####\n.....##\n###.###").err().unwrap();
+        assert_eq!((ErrorKind::StartPosOnBlockedCase, "'#'"), (grid_error.kind, grid_error.found.as_str()));
+        let grid_error = GridInfo::new("legend: .=open #=lava\n2 6\n0 2\nOXOEEEE\nOOOOOEE\nEOOEEEE").err().unwrap();
+        assert_eq!((ErrorKind::WrongLegend, 1, 16), (grid_error.kind, grid_error.line, grid_error.column));
+        assert_eq!("\"#=lava\"", grid_error.found);
+    }
+    #[test]
+    fn test_should_split_a_stream_of_maps() {
+        let stream = "2 1\n0 0\nOX\nOE\nOE\n---\n# second map\nSOX\n---\n\n";
+        let maps = GridInfo::split_maps(stream);
+        assert_eq!(vec![(0, "2 1\n0 0\nOX\nOE\nOE".to_string()), (6, "# second map\nSOX".to_string())], maps);
+        assert!(GridInfo::new(&maps[1].1).is_ok());
+        assert_eq!(vec![(0, "SOX".to_string())], GridInfo::split_maps("SOX\n"));
+    }
 }
@@ -0,0 +1,350 @@
+use crate::grid_info::grid;
+use crate::GridInfo;
+use std::fmt;
+
+/// Problem found while reading an image or turning it into a grid
+#[derive(PartialEq, Debug)]
+pub enum Error {
+    /// not a PPM, PGM or PNG file, or a part of it is missing
+    WrongImage(String),
+    /// pixel whose color is not in the color mapping, x from the left and y from the top
+    UnknownColor { x: usize, y: usize, color: [u8; 3] },
+    /// the image is not a valid map, the line of the error is y + 1 and its column x + 1
+    Grid(grid::Error),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::WrongImage(reason) => write!(f, "wrong image: {}", reason),
+            Error::UnknownColor { x, y, color } => {
+                write!(f, "pixel ({}, {}): color {} is not in the color mapping", x, y, ColorMapping::hex(*color))
+            }
+            Error::Grid(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// Colors of the cases in an image
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ColorMapping {
+    pub open: [u8; 3],
+    pub wall: [u8; 3],
+    pub start: [u8; 3],
+    pub exit: [u8; 3],
+    /// only drawn when a solved map is exported
+    pub path: [u8; 3],
+}
+
+impl Default for ColorMapping {
+    fn default() -> ColorMapping {
+        ColorMapping {
+            open: [255, 255, 255],
+            wall: [0, 0, 0],
+            start: [0, 255, 0],
+            exit: [255, 0, 0],
+            path: [0, 0, 255],
+        }
+    }
+}
+
+impl ColorMapping {
+    pub const EXPECTED_ENTRY: &'static str = "`case=#rrggbb` with a case among open, wall, start, exit or path";
+
+    /// Mapping from entries separated by spaces such as `open=#ffffff wall=#000000 path=#ffff00`,
+    /// the cases left out keep their default color. Returns the first wrong entry otherwise
+    pub fn new(entries: &str) -> Result<ColorMapping, &str> {
+        let mut color_mapping = ColorMapping::default();
+        for entry in entries.split_whitespace() {
+            let (case, hex) = entry.split_once("=#").ok_or(entry)?;
+            if hex.len() != 6 || !hex.is_ascii() {
+                return Err(entry);
+            }
+            let mut color = [0; 3];
+            for (channel, channel_hex) in color.iter_mut().zip([&hex[0..2], &hex[2..4], &hex[4..6]]) {
+                *channel = u8::from_str_radix(channel_hex, 16).map_err(|_| entry)?;
+            }
+            match case {
+                "open" => color_mapping.open = color,
+                "wall" => color_mapping.wall = color,
+                "start" => color_mapping.start = color,
+                "exit" => color_mapping.exit = color,
+                "path" => color_mapping.path = color,
+                _ => return Err(entry),
+            }
+        }
+        Ok(color_mapping)
+    }
+
+    fn hex(color: [u8; 3]) -> String {
+        format!("#{:02x}{:02x}{:02x}", color[0], color[1], color[2])
+    }
+
+    /// Case of a map without header ('S' for a start), None for an unknown color.
+    /// A path is read as open cases so that a solved map can be read back
+    fn case(&self, color: [u8; 3]) -> Option<char> {
+        [
+            (self.start, GridInfo::CASE_START),
+            (self.exit, GridInfo::CASE_WIN),
+            (self.wall, GridInfo::CASE_CLOSE),
+            (self.open, GridInfo::CASE_OPEN),
+            (self.path, GridInfo::CASE_OPEN),
+        ]
+        .into_iter()
+        .find(|&(case_color, _)| case_color == color)
+        .map(|(_, case)| case)
+    }
+}
+
+/// RGB image, pixels from the top left corner row by row
+#[derive(Clone, Debug, PartialEq)]
+pub struct Image {
+    pub width: usize,
+    pub height: usize,
+    pub pixels: Vec<[u8; 3]>,
+}
+
+impl Image {
+    const PNG_SIGNATURE: [u8; 8] = [137, 80, 78, 71, 13, 10, 26, 10];
+
+    /// Whether a file is read as an image, from its extension
+    pub fn is_image_file(file_name: &str) -> bool {
+        let file_name = file_name.to_ascii_lowercase();
+        [".ppm", ".pgm", ".pnm", ".png"].iter().any(|extension| file_name.ends_with(extension))
+    }
+
+    /// Read a PNG, or a PPM / PGM file in its plain (P3 / P2) or raw (P6 / P5) form
+    pub fn new(bytes: &[u8]) -> Result<Image, Error> {
+        if bytes.starts_with(&Image::PNG_SIGNATURE) {
+            Image::from_png(bytes)
+        } else {
+            Image::from_pnm(bytes)
+        }
+    }
+
+    fn from_pnm(bytes: &[u8]) -> Result<Image, Error> {
+        let mut position = 0;
+        // header fields are separated by whitespaces, a '#' starts a comment up to the end of the line
+        let next_field = |position: &mut usize| -> Option<String> {
+            loop {
+                while *position < bytes.len() && bytes[*position].is_ascii_whitespace() {
+                    *position += 1;
+                }
+                if *position < bytes.len() && bytes[*position] == b'#' {
+                    while *position < bytes.len() && bytes[*position] != b'\n' {
+                        *position += 1;
+                    }
+                } else {
+                    break;
+                }
+            }
+            let field_start = *position;
+            while *position < bytes.len() && !bytes[*position].is_ascii_whitespace() {
+                *position += 1;
+            }
+            (field_start < *position).then(|| String::from_utf8_lossy(&bytes[field_start..*position]).to_string())
+        };
+        let missing = |part: &str| Error::WrongImage(format!("missing or wrong {}", part));
+
+        let magic_number = next_field(&mut position).ok_or(missing("magic number"))?;
+        let (channels, plain) = match magic_number.as_str() {
+            "P2" => (1, true),
+            "P3" => (3, true),
+            "P5" => (1, false),
+            "P6" => (3, false),
+            _ => return Err(Error::WrongImage(format!("unsupported format {}, expected P2, P3, P5, P6 or PNG", magic_number))),
+        };
+        let read_number = |position: &mut usize, part: &str| {
+            next_field(position).and_then(|field| field.parse::<usize>().ok()).ok_or(missing(part))
+        };
+        let width = read_number(&mut position, "width")?;
+        let height = read_number(&mut position, "height")?;
+        let max_value = read_number(&mut position, "max value")?;
+        if max_value == 0 || max_value > 255 {
+            return Err(Error::WrongImage(format!("max value {} not supported, expected 1 to 255", max_value)));
+        }
+
+        let sample_count = width
+            .checked_mul(height)
+            .and_then(|pixel_count| pixel_count.checked_mul(channels))
+            .ok_or(Error::WrongImage(format!("size {} x {} too large", width, height)))?;
+        let samples: Vec<usize> = if plain {
+            (0..sample_count)
+                .map(|_| read_number(&mut position, "pixel"))
+                .collect::<Result<_, _>>()?
+        } else {
+            // a single whitespace comes between the max value and the pixels
+            let pixels_end = (position + 1).checked_add(sample_count).ok_or(missing("pixel"))?;
+            let pixels = bytes.get(position + 1..pixels_end).ok_or(missing("pixel"))?;
+            pixels.iter().map(|&sample| sample as usize).collect()
+        };
+        // scaled to 255 so that the colors do not depend on the max value
+        let samples: Vec<u8> = samples.iter().map(|&sample| (sample.min(max_value) * 255 / max_value) as u8).collect();
+        Ok(Image::from_samples(width, height, &samples, channels))
+    }
+
+    fn from_png(bytes: &[u8]) -> Result<Image, Error> {
+        let png_error = |error: png::DecodingError| Error::WrongImage(error.to_string());
+        let mut decoder = png::Decoder::new(bytes);
+        // palettes and small bit depths are expanded to 8 bits per sample, 16 bits are cut to 8
+        decoder.set_transformations(png::Transformations::EXPAND | png::Transformations::STRIP_16);
+        let mut reader = decoder.read_info().map_err(png_error)?;
+        let mut buffer = vec![0; reader.output_buffer_size()];
+        let output_info = reader.next_frame(&mut buffer).map_err(png_error)?;
+        let channels = match output_info.color_type {
+            png::ColorType::Grayscale => 1,
+            png::ColorType::GrayscaleAlpha => 2,
+            png::ColorType::Rgb | png::ColorType::Indexed => 3,
+            png::ColorType::Rgba => 4,
+        };
+        Ok(Image::from_samples(
+            output_info.width as usize,
+            output_info.height as usize,
+            &buffer[..output_info.buffer_size()],
+            channels,
+        ))
+    }
+
+    /// 1 channel is gray, 2 gray and alpha, 3 RGB and 4 RGBA (the alpha is ignored)
+    fn from_samples(width: usize, height: usize, samples: &[u8], channels: usize) -> Image {
+        let pixels = samples
+            .chunks_exact(channels)
+            .map(|pixel| if channels < 3 { [pixel[0]; 3] } else { [pixel[0], pixel[1], pixel[2]] })
+            .collect();
+        Image { width, height, pixels }
+    }
+
+    /// Grid of the image, one pixel per case and the top row of the image as the top row of the grid
+    pub fn grid_info(&self, color_mapping: &ColorMapping) -> Result<GridInfo, Error> {
+        let mut grid_row = String::new();
+        for (y, pixel_row) in self.pixels.chunks(self.width.max(1)).enumerate() {
+            for (x, &color) in pixel_row.iter().enumerate() {
+                grid_row.push(color_mapping.case(color).ok_or(Error::UnknownColor { x, y, color })?);
+            }
+            grid_row.push('\n');
+        }
+        // a map without header, whose line and column are those of the pixel
+        GridInfo::new(&grid_row).map_err(Error::Grid)
+    }
+
+    /// Image of a grid with its paths drawn, the weighted cases are drawn open
+    pub fn from_grid_info(grid_info: &GridInfo, paths: &[Vec<(usize, usize)>], color_mapping: &ColorMapping) -> Image {
+        let (width, height) = (grid_info.column_max + 1, grid_info.row_max + 1);
+        let mut pixels = vec![color_mapping.open; width * height];
+        let pixel_index = |pos: (usize, usize)| (grid_info.row_max - pos.0) * width + pos.1;
+        for (row, grid_line) in grid_info.grid.iter().enumerate() {
+            for (column, &case) in grid_line.iter().enumerate() {
+                if GridInfo::case_cost(case).is_none() {
+                    pixels[pixel_index((row, column))] = color_mapping.wall;
+                }
+            }
+        }
+        for &pos in paths.iter().flatten() {
+            pixels[pixel_index(pos)] = color_mapping.path;
+        }
+        // drawn last so that the ends of the paths keep their color
+        for &exit_pos in &grid_info.exit_positions {
+            pixels[pixel_index(exit_pos)] = color_mapping.exit;
+        }
+        for &start_pos in &grid_info.start_positions {
+            pixels[pixel_index(start_pos)] = color_mapping.start;
+        }
+        Image { width, height, pixels }
+    }
+
+    /// Raw PPM (P6) file of the image
+    pub fn to_ppm(&self) -> Vec<u8> {
+        let mut bytes = format!("P6\n{} {}\n255\n", self.width, self.height).into_bytes();
+        bytes.extend(self.pixels.iter().flatten());
+        bytes
+    }
+
+    /// RGB PNG file of the image
+    pub fn to_png(&self) -> Vec<u8> {
+        let mut bytes = vec![];
+        let mut encoder = png::Encoder::new(&mut bytes, self.width as u32, self.height as u32);
+        encoder.set_color(png::ColorType::Rgb);
+        encoder.set_depth(png::BitDepth::Eight);
+        // writing to memory only fails on a wrong size, which the image cannot have
+        let mut writer = encoder.write_header().unwrap();
+        writer.write_image_data(&self.pixels.concat()).unwrap();
+        writer.finish().unwrap();
+        bytes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid_info::grid::ErrorKind;
+    use std::collections::BTreeSet;
+
+    #[test]
+    fn test_should_read_a_plain_ppm_into_a_grid() {
+        let ppm = b"P3\n# two ways\n4 2\n255\n0 0 0  255 0 0  255 255 255  0 0 0\n255 255 255  255 255 255  0 255 0  0 0 0\n";
+        let grid_info = Image::new(ppm).unwrap().grid_info(&ColorMapping::default()).unwrap();
+        assert_eq!((1, 3), (grid_info.row_max, grid_info.column_max));
+        assert_eq!(vec![(0, 2)], grid_info.start_positions);
+        assert_eq!(BTreeSet::from([(1, 1)]), grid_info.exit_positions);
+        assert_eq!(vec![vec!['O', 'O', 'O', 'E'], vec!['E', 'X', 'O', 'E']], grid_info.grid);
+    }
+    #[test]
+    fn test_should_read_plain_and_raw_pgm_with_their_max_value() {
+        let plain_pgm = b"P2\n3 1\n15\n0 15 7\n";
+        assert_eq!(vec![[0; 3], [255; 3], [119; 3]], Image::new(plain_pgm).unwrap().pixels);
+        let raw_pgm = [b"P5 3 1 255\n".as_slice(), &[0, 255, 119]].concat();
+        assert_eq!(vec![[0; 3], [255; 3], [119; 3]], Image::new(&raw_pgm).unwrap().pixels);
+        let color_mapping = ColorMapping::new("start=#777777 exit=#ffffff open=#010101").unwrap();
+        let grid_info = Image::new(&raw_pgm).unwrap().grid_info(&color_mapping).unwrap();
+        assert_eq!(vec![vec!['E', 'X', 'O']], grid_info.grid);
+    }
+    #[test]
+    fn test_should_return_errors_for_wrong_images() {
+        assert!(matches!(Image::new(b"P4\n1 1\n"), Err(Error::WrongImage(_))));
+        assert!(matches!(Image::new(b"P6\n2 2\n255\n\x00\x00\x00"), Err(Error::WrongImage(_))));
+        assert!(matches!(Image::new(b"P6\n18446744073709551615 2\n255\n"), Err(Error::WrongImage(_))));
+        assert!(matches!(Image::new(b"P5\n18446744073709551615 1\n255\n"), Err(Error::WrongImage(_))));
+        let ppm = b"P3 2 1 255 0 0 0 1 2 3";
+        assert_eq!(
+            Some(Error::UnknownColor { x: 1, y: 0, color: [1, 2, 3] }),
+            Image::new(ppm).unwrap().grid_info(&ColorMapping::default()).err()
+        );
+        let ppm = b"P3 2 1 255 0 255 0 255 255 255";
+        match Image::new(ppm).unwrap().grid_info(&ColorMapping::default()).err() {
+            Some(Error::Grid(grid_error)) => assert_eq!(ErrorKind::MisingExit, grid_error.kind),
+            other => panic!("Expected a grid error, but got {:?}", other),
+        }
+        assert_eq!(Err("wall=#00000g"), ColorMapping::new("open=#ffffff wall=#00000g"));
+        assert_eq!(Err("lava=#ff0000"), ColorMapping::new("lava=#ff0000"));
+    }
+    #[test]
+    fn test_exported_images_should_draw_the_path_and_be_read_back() {
+        let grid_info = GridInfo::new("2 3\n0 0\nEOOX\nOEOE\nO9OE\n").unwrap();
+        let color_mapping = ColorMapping::default();
+        let image = Image::from_grid_info(&grid_info, &[vec![(0, 0), (1, 0), (2, 1), (2, 2), (2, 3)]], &color_mapping);
+        let [open, wall, start, exit, path] = [
+            color_mapping.open,
+            color_mapping.wall,
+            color_mapping.start,
+            color_mapping.exit,
+            color_mapping.path,
+        ];
+        assert_eq!(
+            vec![wall, path, path, exit, path, wall, open, wall, start, open, open, wall],
+            image.pixels
+        );
+        for bytes in [image.to_ppm(), image.to_png()] {
+            assert_eq!(image, Image::new(&bytes).unwrap());
+        }
+        // the path is read back as open cases, and the weighted case as well
+        assert_eq!(vec!['O', 'O', 'O', 'E'], image.grid_info(&color_mapping).unwrap().grid[0]);
+        let mut unsolved_image = Image::from_grid_info(&grid_info, &[], &color_mapping);
+        let grid_info_read_back = unsolved_image.grid_info(&color_mapping).unwrap();
+        assert_eq!(grid_info.start_positions, grid_info_read_back.start_positions);
+        assert_eq!(grid_info.exit_positions, grid_info_read_back.exit_positions);
+        unsolved_image.pixels[9] = [9, 9, 9];
+        assert!(matches!(unsolved_image.grid_info(&color_mapping), Err(Error::UnknownColor { x: 1, y: 2, .. })));
+    }
+}
@@ -0,0 +1,239 @@
+use crate::path_detective::{Connectivity, Heuristic, Metric, PathResult};
+use crate::pathfinder::{AStar, OpenCase, Pathfinder, SearchGrid};
+use crate::GridInfo;
+use std::collections::{BTreeSet, BinaryHeap};
+
+/// Jump Point Search: A* on uniform-cost Moore grids which jumps along straight and diagonal lines
+/// and only expands the cases where a shortest path may turn (the jump points).
+/// Grids with weighted cases or another connectivity are solved by A* instead
+pub struct JumpPointSearch;
+
+/// Direction of a move as (row offset, column offset), each one -1, 0 or 1
+type Direction = (isize, isize);
+
+impl JumpPointSearch {
+    /// Whether the pruning rules of JPS keep every shortest path of the grid
+    fn is_supported(search_grid: &SearchGrid) -> bool {
+        search_grid.connectivity == Connectivity::Moore
+            && search_grid
+                .grid_info
+                .grid
+                .iter()
+                .flatten()
+                .all(|&case| GridInfo::case_cost(case).is_none_or(|cost| cost == 1))
+    }
+
+    fn heuristic(metric: Metric) -> Heuristic {
+        match metric {
+            Metric::Chebyshev => Heuristic::Chebyshev,
+            Metric::Octile => Heuristic::Octile,
+        }
+    }
+
+    /// Case one move away in the direction, if it is in the grid
+    fn step(search_grid: &SearchGrid, pos: (usize, usize), direction: Direction) -> Option<(usize, usize)> {
+        let row = pos.0.checked_add_signed(direction.0)?;
+        let column = pos.1.checked_add_signed(direction.1)?;
+        search_grid.is_valid_case(row, column).then_some((row, column))
+    }
+
+    fn is_blocked(search_grid: &SearchGrid, pos: (usize, usize), direction: Direction) -> bool {
+        JumpPointSearch::step(search_grid, pos, direction).is_none()
+    }
+
+    /// Directions worth following from a case reached with `direction`: the natural ones, plus the forced ones
+    /// whose case can only be reached optimally through this case because of a blocked case next to it
+    fn pruned_directions(search_grid: &SearchGrid, pos: (usize, usize), direction: Direction) -> Vec<Direction> {
+        let (row_offset, column_offset) = direction;
+        let mut directions = vec![direction];
+        if row_offset != 0 && column_offset != 0 {
+            directions.extend([(row_offset, 0), (0, column_offset)]);
+            if JumpPointSearch::is_blocked(search_grid, pos, (-row_offset, 0)) {
+                directions.push((-row_offset, column_offset));
+            }
+            if JumpPointSearch::is_blocked(search_grid, pos, (0, -column_offset)) {
+                directions.push((row_offset, -column_offset));
+            }
+        } else {
+            // the two sides of a straight move
+            let sides = if row_offset == 0 { [(1, 0), (-1, 0)] } else { [(0, 1), (0, -1)] };
+            for side in sides {
+                if JumpPointSearch::is_blocked(search_grid, pos, side) {
+                    directions.push((side.0 + row_offset, side.1 + column_offset));
+                }
+            }
+        }
+        directions
+    }
+
+    /// Whether a case reached with `direction` has a forced direction, without building the directions
+    /// (called on every case of a jump)
+    fn has_forced_direction(search_grid: &SearchGrid, pos: (usize, usize), direction: Direction) -> bool {
+        let (row_offset, column_offset) = direction;
+        let is_forced = |side: Direction, forced_direction: Direction| {
+            JumpPointSearch::is_blocked(search_grid, pos, side) && !JumpPointSearch::is_blocked(search_grid, pos, forced_direction)
+        };
+        if row_offset != 0 && column_offset != 0 {
+            is_forced((-row_offset, 0), (-row_offset, column_offset)) || is_forced((0, -column_offset), (row_offset, -column_offset))
+        } else {
+            let sides = if row_offset == 0 { [(1, 0), (-1, 0)] } else { [(0, 1), (0, -1)] };
+            sides.into_iter().any(|side| is_forced(side, (side.0 + row_offset, side.1 + column_offset)))
+        }
+    }
+
+    /// Next jump point from `pos` in the direction: an exit, a case with a forced direction,
+    /// or for a diagonal move a case from which a straight jump finds one
+    fn jump(
+        search_grid: &SearchGrid,
+        mut pos: (usize, usize),
+        direction: Direction,
+        exit_positions: &BTreeSet<(usize, usize)>,
+    ) -> Option<(usize, usize)> {
+        loop {
+            pos = JumpPointSearch::step(search_grid, pos, direction)?;
+            if exit_positions.contains(&pos) || JumpPointSearch::has_forced_direction(search_grid, pos, direction) {
+                return Some(pos);
+            }
+            if direction.0 != 0
+                && direction.1 != 0
+                && (JumpPointSearch::jump(search_grid, pos, (direction.0, 0), exit_positions).is_some()
+                    || JumpPointSearch::jump(search_grid, pos, (0, direction.1), exit_positions).is_some())
+            {
+                return Some(pos);
+            }
+        }
+    }
+
+    /// Every case between consecutive jump points, which are on a straight or diagonal line
+    fn fill_path(jump_points: &[(usize, usize)]) -> Vec<(usize, usize)> {
+        let mut path = vec![jump_points[0]];
+        for segment in jump_points.windows(2) {
+            let mut pos = segment[0];
+            let row_offset = (segment[1].0 as isize - pos.0 as isize).signum();
+            let column_offset = (segment[1].1 as isize - pos.1 as isize).signum();
+            while pos != segment[1] {
+                pos = ((pos.0 as isize + row_offset) as usize, (pos.1 as isize + column_offset) as usize);
+                path.push(pos);
+            }
+        }
+        path
+    }
+}
+
+impl Pathfinder for JumpPointSearch {
+    fn name(&self) -> &'static str {
+        "jps"
+    }
+
+    fn find_path(&self, search_grid: &SearchGrid, start_pos: (usize, usize), exit_positions: &BTreeSet<(usize, usize)>) -> Option<PathResult> {
+        let heuristic = JumpPointSearch::heuristic(search_grid.metric);
+        if !JumpPointSearch::is_supported(search_grid) {
+            return AStar(heuristic).find_path(search_grid, start_pos, exit_positions);
+        }
+        if !search_grid.is_valid_case(start_pos.0, start_pos.1) {
+            return None;
+        }
+        let estimate = |pos| {
+            exit_positions
+                .iter()
+                .map(|&exit_pos| heuristic.distance(pos, exit_pos))
+                .fold(f64::INFINITY, f64::min)
+        };
+        let mut costs: Vec<f64> = vec![f64::INFINITY; search_grid.case_count()];
+        // previous jump point of every jump point reached
+        let mut parents: Vec<usize> = vec![usize::MAX; search_grid.case_count()];
+        let mut open_cases: BinaryHeap<OpenCase> = BinaryHeap::new();
+
+        costs[search_grid.index(start_pos)] = 0.0;
+        open_cases.push(OpenCase {
+            estimated_cost: estimate(start_pos),
+            cost_from_start: 0.0,
+            pos: start_pos,
+        });
+        while let Some(OpenCase { cost_from_start, pos, .. }) = open_cases.pop() {
+            if exit_positions.contains(&pos) {
+                let jump_points = search_grid.rebuild_path(&parents, pos);
+                return Some(search_grid.path_result(JumpPointSearch::fill_path(&jump_points)));
+            }
+            if cost_from_start > costs[search_grid.index(pos)] {
+                continue;
+            }
+            search_grid.count_expanded_case();
+            let directions: Vec<Direction> = match parents[search_grid.index(pos)] {
+                // every move is followed from the start
+                usize::MAX => search_grid
+                    .connected_cases(pos.0, pos.1)
+                    .map(|connected_pos| (connected_pos.0 as isize - pos.0 as isize, connected_pos.1 as isize - pos.1 as isize))
+                    .collect(),
+                parent_index => {
+                    let parent_pos = search_grid.pos(parent_index);
+                    let direction = ((pos.0 as isize - parent_pos.0 as isize).signum(), (pos.1 as isize - parent_pos.1 as isize).signum());
+                    JumpPointSearch::pruned_directions(search_grid, pos, direction)
+                }
+            };
+            for direction in directions {
+                let Some(jump_pos) = JumpPointSearch::jump(search_grid, pos, direction, exit_positions) else {
+                    continue;
+                };
+                let steps = pos.0.abs_diff(jump_pos.0).max(pos.1.abs_diff(jump_pos.1));
+                let move_length = search_grid.metric.move_length((0, 0), (direction.0.unsigned_abs(), direction.1.unsigned_abs()));
+                let cost = cost_from_start + steps as f64 * move_length;
+                let jump_index = search_grid.index(jump_pos);
+                if cost < costs[jump_index] {
+                    costs[jump_index] = cost;
+                    parents[jump_index] = search_grid.index(pos);
+                    open_cases.push(OpenCase {
+                        estimated_cost: cost + estimate(jump_pos),
+                        cost_from_start: cost,
+                        pos: jump_pos,
+                    });
+                }
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pathfinder::tests::solve;
+    use crate::pathfinder::Bfs;
+
+    #[test]
+    fn test_jps_path_goes_through_every_case() {
+        let rows = "OOOOOOOX\nOEEEEEEE\nSOOOOOOO";
+        let (path_result, _) = solve(rows, &JumpPointSearch, Metric::Octile);
+        let path_result = path_result.unwrap();
+        assert_eq!((2, 7), path_result.exit_pos);
+        assert_eq!(path_result.path.len() - 1, path_result.path.windows(2).filter(|step| step[0].0.abs_diff(step[1].0) <= 1 && step[0].1.abs_diff(step[1].1) <= 1).count());
+        assert_eq!(Some(8.0), solve(rows, &Bfs, Metric::Chebyshev).0.map(|path_result| path_result.length));
+        assert!((path_result.length - (7.0 + std::f64::consts::SQRT_2)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_jps_expands_fewer_cases_than_bfs_on_an_open_map() {
+        let rows = ["SOOOOOOOOOOOOOOOOOOO"].into_iter().chain(["OOOOOOOOOOOOOOOOOOOO"; 18]).chain(["OOOOOOOOOOOOOOOOOOOX"]).collect::<Vec<_>>().join("\n");
+        let (jps_result, jps_expanded) = solve(&rows, &JumpPointSearch, Metric::Chebyshev);
+        let (bfs_result, bfs_expanded) = solve(&rows, &Bfs, Metric::Chebyshev);
+        assert_eq!(bfs_result.unwrap().length, jps_result.unwrap().length);
+        assert!(jps_expanded * 10 < bfs_expanded, "{} {}", jps_expanded, bfs_expanded);
+    }
+
+    #[test]
+    fn test_jps_should_find_the_forced_way_around_a_wall() {
+        // the only way goes down around the end of the wall, then back up
+        let rows = "SOEOX\nOOEOO\nOOEOO\nOOOOE";
+        for metric in [Metric::Chebyshev, Metric::Octile] {
+            let (jps_result, _) = solve(rows, &JumpPointSearch, metric);
+            let (astar_result, _) = solve(rows, &AStar(JumpPointSearch::heuristic(metric)), metric);
+            assert_eq!(astar_result.unwrap().length, jps_result.unwrap().length);
+        }
+    }
+
+    #[test]
+    fn test_jps_falls_back_to_astar_on_weighted_cases() {
+        let (path_result, _) = solve("S99X\nOOOO", &JumpPointSearch, Metric::Chebyshev);
+        assert_eq!(vec![(1, 0), (0, 1), (0, 2), (1, 3)], path_result.unwrap().path);
+    }
+}
@@ -0,0 +1,138 @@
+use crate::GridInfo;
+use std::collections::BTreeMap;
+
+/// Characters of a map file and the case each one stands for.
+/// The grid always holds the cases of `GridInfo` ('O', 'E', 'X', '1' to '9'),
+/// the legend is only followed to read, write and draw the map
+#[derive(Clone, PartialEq, Debug)]
+pub struct Legend {
+    /// map file character to case
+    cases: BTreeMap<char, char>,
+    /// case to the map file character, the first one given in the legend
+    symbols: BTreeMap<char, char>,
+}
+
+impl Default for Legend {
+    fn default() -> Legend {
+        let cases: BTreeMap<char, char> = [GridInfo::CASE_OPEN, GridInfo::CASE_CLOSE, GridInfo::CASE_WIN, GridInfo::CASE_START]
+            .into_iter()
+            .chain(GridInfo::CASE_COST_MIN..=GridInfo::CASE_COST_MAX)
+            .map(|case| (case, case))
+            .collect();
+        Legend {
+            symbols: cases.clone(),
+            cases,
+        }
+    }
+}
+
+impl Legend {
+    /// metadata key of the legend in the map file header
+    pub const KEY: &'static str = "legend";
+    pub const EXPECTED_ENTRY: &'static str =
+        "`symbol=case` with a symbol other than ':' and '-' standing for a single case, and a case among open, wall, exit, start or a cost from 1 to 9";
+    /// symbols of the metadata lines and of the `---` lines between the maps of a stream
+    const RESERVED_SYMBOLS: [char; 2] = [GridInfo::METADATA_SEPARATOR, '-'];
+
+    /// Legend from entries separated by spaces such as `.=open #=wall @=start >=exit ~=3`,
+    /// the characters left out keep their default meaning. Returns the first wrong entry otherwise,
+    /// or the entry which gives the symbol of a case to another one
+    pub fn new(entries: &str) -> Result<Legend, &str> {
+        let mut legend = Legend::default();
+        let mut customized_cases = vec![];
+        for entry in entries.split_whitespace() {
+            let mut entry_chars = entry.chars();
+            let symbol = entry_chars.next().filter(|symbol| !Legend::RESERVED_SYMBOLS.contains(symbol)).ok_or(entry)?;
+            let case = match entry_chars.as_str().strip_prefix('=').ok_or(entry)? {
+                "open" => GridInfo::CASE_OPEN,
+                "wall" => GridInfo::CASE_CLOSE,
+                "exit" => GridInfo::CASE_WIN,
+                "start" => GridInfo::CASE_START,
+                cost if cost.len() == 1 && GridInfo::case_cost(cost.chars().next().unwrap()).is_some() => cost.chars().next().unwrap(),
+                _ => return Err(entry),
+            };
+            legend.cases.insert(symbol, case);
+            if !customized_cases.contains(&case) {
+                legend.symbols.insert(case, symbol);
+                customized_cases.push(case);
+            }
+        }
+        // a case must be read back from the symbol it is written with
+        for (&case, &symbol) in &legend.symbols {
+            if legend.case(symbol) != case {
+                return Err(entries.split_whitespace().find(|entry| entry.starts_with(symbol)).unwrap_or(entries));
+            }
+        }
+        Ok(legend)
+    }
+
+    /// Case of a map file character, an unknown character is kept as it is
+    pub fn case(&self, symbol: char) -> char {
+        self.cases.get(&symbol).copied().unwrap_or(symbol)
+    }
+
+    /// Map file character of a case
+    pub fn symbol(&self, case: char) -> char {
+        self.symbols.get(&case).copied().unwrap_or(case)
+    }
+
+    pub fn has_symbol(&self, symbol: char) -> bool {
+        self.cases.contains_key(&symbol)
+    }
+
+    /// Map file characters allowed in a grid, for error messages
+    pub fn expected_cases(&self) -> String {
+        format!(
+            "one of '{}', '{}', '{}' or a cost from '{}' to '{}'",
+            self.symbol(GridInfo::CASE_OPEN),
+            self.symbol(GridInfo::CASE_CLOSE),
+            self.symbol(GridInfo::CASE_WIN),
+            self.symbol(GridInfo::CASE_COST_MIN),
+            self.symbol(GridInfo::CASE_COST_MAX)
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn test_default_legend_keeps_every_case() {
+        let legend = Legend::default();
+        assert_eq!(GridInfo::CASE_OPEN, legend.case('O'));
+        assert_eq!('7', legend.symbol('7'));
+        assert_eq!('.', legend.case('.'));
+        assert!(!legend.has_symbol('#'));
+        assert_eq!("one of 'O', 'E', 'X' or a cost from '1' to '9'", legend.expected_cases());
+    }
+    #[test]
+    fn test_legend_maps_symbols_to_cases_and_back() {
+        let legend = Legend::new(".=open #=wall T=wall @=start >=exit ~=3").unwrap();
+        assert_eq!(GridInfo::CASE_OPEN, legend.case('.'));
+        assert_eq!(GridInfo::CASE_CLOSE, legend.case('T'));
+        assert_eq!(GridInfo::CASE_START, legend.case('@'));
+        assert_eq!('3', legend.case('~'));
+        // the first symbol of a case is used to write it back
+        assert_eq!('#', legend.symbol(GridInfo::CASE_CLOSE));
+        assert_eq!('>', legend.symbol(GridInfo::CASE_WIN));
+        assert_eq!(GridInfo::CASE_OPEN, legend.case('O'));
+    }
+    #[test]
+    fn test_legend_returns_the_wrong_entry() {
+        assert_eq!(Err("#=lava"), Legend::new(".=open #=lava"));
+        assert_eq!(Err("#wall"), Legend::new("#wall"));
+        assert_eq!(Err("~=0"), Legend::new("~=0"));
+    }
+    #[test]
+    fn test_legend_rejects_the_symbols_of_metadata_and_map_delimiter_lines() {
+        assert_eq!(Err(":=3"), Legend::new(".=open #=wall :=3"));
+        assert_eq!(Err("-=wall"), Legend::new("-=wall"));
+    }
+    #[test]
+    fn test_legend_rejects_a_symbol_left_to_another_case() {
+        // the exit would be written 'X' and read back as a wall
+        assert_eq!(Err("X=wall"), Legend::new("X=wall"));
+        assert_eq!(Err("E=open"), Legend::new(".=open E=open"));
+        assert!(Legend::new("X=wall >=exit").is_ok());
+    }
+}
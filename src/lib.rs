@@ -0,0 +1,33 @@
+//! Wazelentin finds the shortest way from every start of a grid map to its nearest exit.
+//!
+//! - [`GridInfo`] parses and writes map files (see [`GridInfo::new`] for the format),
+//!   [`moving_ai`], [`image_map`] and [`tiled_map`] convert other map formats to it
+//! - [`PathDetective`] solves a grid with a [`Pathfinder`]: BFS, bidirectional BFS, Dijkstra, A*, Jump Point Search
+//!   or Theta* for any-angle paths, picked by name with [`SearchMode::from_name`]
+//! - [`DisplayGrid`] draws a grid and the cases of a search in the terminal
+//!
+//! ```
+//! use wazelentin::{GridInfo, PathDetective};
+//!
+//! let grid_info = GridInfo::new("2 2\n0 0\nOOX\nOEE\nOOO").unwrap();
+//! let path_result = PathDetective::new(grid_info, None).find_and_transmit_path().unwrap();
+//! assert_eq!(vec![(0, 0), (1, 0), (2, 1), (2, 2)], path_result.path);
+//! ```
+
+pub mod bidirectional_bfs;
+pub mod grid_display;
+pub mod grid_info;
+pub mod image_map;
+pub mod jump_point_search;
+pub mod legend;
+pub mod moving_ai;
+pub mod path_detective;
+pub mod pathfinder;
+pub mod solve_report;
+pub mod theta_star;
+pub mod tiled_map;
+
+pub use grid_display::DisplayGrid;
+pub use grid_info::GridInfo;
+pub use path_detective::{Connectivity, Heuristic, Metric, PathDetective, PathResult, SearchMode};
+pub use pathfinder::{Pathfinder, SearchGrid};
@@ -1,48 +1,289 @@
-mod grid_info;
-mod path_detective;
-mod grid_display;
-
 use std::thread;
 use clap::Parser;
 use std::sync::mpsc;
+use std::time::Instant;
+
+use wazelentin::grid_info::GridInfo;
+use wazelentin::path_detective::{Connectivity, Heuristic, Metric, PathDetective, SearchMode};
+use wazelentin::grid_display::DisplayGrid;
+use wazelentin::solve_report::{ErrorReport, PathReport, SolveReport};
+use wazelentin::moving_ai::{self, MovingAiMap};
+use wazelentin::image_map::{ColorMapping, Image};
+use wazelentin::tiled_map::TiledMap;
+use wazelentin::theta_star;
 
-use grid_info::GridInfo;
-use path_detective::PathDetective;
-use grid_display::DisplayGrid;
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq)]
+enum OutputFormat {
+    /// terminal animation then a summary line per start
+    Text,
+    /// a JSON document per map with the path of every start, or its error, without animation
+    Json,
+}
 
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
 struct Args {
-    /// path to "map file"
+    /// path to "map file", to a .ppm, .pgm or .png image with one pixel per case (see --colors),
+    /// or to a Tiled .tmx / .tmj / .json map (see --tiled-layer).
+    /// "-" reads map files from the standard input, several maps being separated by "---" lines
     #[arg(short, long)]
     grid_file: String,
+    /// algorithm used to find the shortest path: bfs (breadth first search on the whole grid),
+    /// astar (guided by --heuristic, stops as soon as the exit is reached)
+    /// dijkstra (cheapest path on weighted cases '1' to '9')
+    /// jps (Jump Point Search, faster than astar on open maps without weighted cases)
+    /// bidirectional (breadth first search from the start and the exits until they meet)
+    /// or theta (Theta*, any-angle path through waypoints in line of sight, with its Euclidean length,
+    /// which never cuts the corner of a blocked case).
+    /// bfs by default, astar with --scenario
+    #[arg(short, long, value_parser = clap::builder::PossibleValuesParser::new(SearchMode::NAMES))]
+    search: Option<String>,
+    /// heuristic used by the A* search
+    #[arg(long, value_enum, default_value_t = Heuristic::Chebyshev)]
+    heuristic: Heuristic,
+    /// length of a diagonal move: 1 with chebyshev, √2 with octile (followed by astar, dijkstra and jps)
+    #[arg(short, long, value_enum, default_value_t = Metric::Chebyshev)]
+    metric: Metric,
+    /// moves allowed from a case
+    #[arg(short, long, value_enum, default_value_t = Connectivity::Moore)]
+    connectivity: Connectivity,
+    /// solve without the terminal animation and print only the path
+    #[arg(short = 'q', long, visible_alias = "quiet")]
+    no_display: bool,
+    /// format of the result
+    #[arg(short, long, value_enum, default_value_t = OutputFormat::Text)]
+    format: OutputFormat,
+    /// only check the map file, report every error found and exit non-zero if there is any
+    #[arg(long)]
+    validate: bool,
+    /// write the map read from --grid-file to this file in the map format, without solving it
+    /// (a FILE ending with .map is written in the MovingAI format, with its scenarios in FILE.scen,
+    /// and a FILE ending with .ppm or .png as an image)
+    #[arg(long, value_name = "FILE")]
+    save_map: Option<String>,
+    /// write the solved map to this .ppm or .png image, with the paths drawn
+    #[arg(long, value_name = "FILE")]
+    save_image: Option<String>,
+    /// colors of the cases in the images, e.g. "open=#ffffff wall=#000000 start=#00ff00 exit=#ff0000 path=#0000ff"
+    #[arg(long, default_value = "")]
+    colors: String,
+    /// tile layer of a Tiled map whose tiles block their case, the start and exit cases are
+    /// the objects named (or of type / class) "start" and "exit"
+    #[arg(long, default_value = TiledMap::COLLISION_LAYER)]
+    tiled_layer: String,
+    /// run the scenarios of this MovingAI .scen file on the MovingAI .map given by --grid-file
+    /// (octile moves without corner cutting, searched with astar unless --search gives another mode)
+    #[arg(long, value_name = "FILE")]
+    scenario: Option<String>,
 }
 
+/// --grid-file value reading the map from the standard input
+const STDIN_FILE: &str = "-";
+
 fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let grid_file: String = Args::parse().grid_file;
-    println!("Lets run Wazelentin on [{}]", grid_file);
-    let grid_row_file: String = std::fs::read_to_string(grid_file)?;
-    match GridInfo::new(&grid_row_file)
-    {
-        Ok(grid_info) =>
-        {
-            let (sender, receiver) = mpsc::channel::<(usize, usize)>();
-            let grid_info_copy_for_display = grid_info.clone();
-
-            let display = DisplayGrid{grid_info : grid_info_copy_for_display, receiver : Some(receiver)};
-            let handle = thread::spawn(move || {
-                    display.display_grid();
-                });
-            let wazelentin = PathDetective{grid_info, sender : Some(sender)}; 
-            // IN fact the sender is only for the display, if i don't want to display anything i don't want to add a sender, change it ! -> OPTION !!!
-            wazelentin.find_and_transmit_shortest_path();
-            handle.join().unwrap();
-
-        }
-        Err(err) =>
-        {
-            eprintln!("An error has occured [{:?}]", err);
+    let args = Args::parse();
+    let grid_file: String = if args.grid_file == STDIN_FILE { "<stdin>".to_string() } else { args.grid_file.clone() };
+    let display_enabled = !args.no_display
+        && args.format == OutputFormat::Text
+        && !args.validate
+        && args.save_map.is_none()
+        && args.scenario.is_none();
+    if display_enabled {
+        println!("Lets run Wazelentin on [{}]", grid_file);
+    }
+    let color_mapping = ColorMapping::new(&args.colors)
+        .map_err(|entry| format!("wrong color \"{}\", expected {}", entry, ColorMapping::EXPECTED_ENTRY))?;
+    let grid_row_file: String = if args.grid_file == STDIN_FILE {
+        std::io::read_to_string(std::io::stdin())?
+    } else if Image::is_image_file(&grid_file) {
+        // the image and the Tiled map go through the same checks and searches as a map file
+        let image = Image::new(&std::fs::read(&grid_file)?).map_err(|err| format!("{}: {}", grid_file, err))?;
+        image.grid_info(&color_mapping).map_err(|err| format!("{}: {}", grid_file, err))?.to_map_string()
+    } else if TiledMap::is_tiled_file(&grid_file) {
+        let tiled_map = TiledMap::new(&std::fs::read_to_string(&grid_file)?, &args.tiled_layer)
+            .map_err(|err| format!("{}: {}", grid_file, err))?;
+        tiled_map.grid_info().map_err(|err| format!("{}: {}", grid_file, err))?.to_map_string()
+    } else {
+        std::fs::read_to_string(&grid_file)?
+    };
+    let mut maps = GridInfo::split_maps(&grid_row_file);
+    if maps.is_empty() {
+        // an empty file is reported by GridInfo::new as a map without header
+        maps.push((0, grid_row_file.clone()));
+    }
+    if maps.len() > 1 && (args.scenario.is_some() || args.save_map.is_some() || args.save_image.is_some()) {
+        return Err(format!("{} holds {} maps, --scenario, --save-map and --save-image need a single one", grid_file, maps.len()).into());
+    }
+    // the scenarios are solved at their optimal length by default
+    let search_name = args.search.as_deref().unwrap_or(if args.scenario.is_some() { "astar" } else { "bfs" });
+    let search_mode = SearchMode::from_name(search_name, args.heuristic).expect("clap only accepts the names of SearchMode");
+    if let Some(scen_file) = &args.scenario {
+        let moving_ai_map = MovingAiMap::new(&maps[0].1).map_err(|err| format!("{}: {}", grid_file, err))?;
+        let scenarios = moving_ai_map
+            .read_scenarios(&std::fs::read_to_string(scen_file)?)
+            .map_err(|err| format!("{}: {}", scen_file, err))?;
+        let scenario_results = moving_ai_map.run_scenarios(&scenarios, search_mode);
+        for scenario_result in &scenario_results {
+            let scenario = &scenario_result.scenario;
+            println!(
+                "Bucket {} {:?} -> {:?}: length {}, optimal {:.3}{}",
+                scenario.bucket,
+                scenario.start,
+                scenario.goal,
+                scenario_result.length.map_or("none".to_string(), |length| format!("{:.3}", length)),
+                scenario.optimal_length,
+                if scenario_result.is_optimal() { "" } else { " (not optimal)" }
+            );
+        }
+        let nb_optimal = scenario_results.iter().filter(|scenario_result| scenario_result.is_optimal()).count();
+        println!("{}/{} scenarios solved at the optimal length", nb_optimal, scenario_results.len());
+        if nb_optimal != scenario_results.len() {
+            std::process::exit(1);
         }
+        return Ok(());
+    }
+    // one result per map, the errors point at the lines of the whole stream
+    let mut nb_wrong_maps = 0;
+    for (map_index, (first_line, map)) in maps.iter().enumerate() {
+        let map_name = if maps.len() > 1 { format!("{} (map {})", grid_file, map_index + 1) } else { grid_file.clone() };
+        let mut errors = if args.validate { GridInfo::validate(map) } else { GridInfo::new(map).err().into_iter().collect() };
+        for err in &mut errors {
+            err.line += first_line;
+            eprintln!("{}\n", err.diagnostic(&grid_file, &grid_row_file));
+        }
+        if map_index > 0 && args.format == OutputFormat::Text {
+            println!("{}", GridInfo::MAP_DELIMITER);
+        }
+        if !errors.is_empty() {
+            nb_wrong_maps += 1;
+            if args.validate {
+                eprintln!("{} error(s) found in {}", errors.len(), map_name);
+                println!("{} is not a valid map", map_name);
+            } else if args.format == OutputFormat::Json {
+                println!("{}", ErrorReport::new(&map_name, &errors[0]).to_json());
+            } else {
+                println!("{}: {}", map_name, errors[0]);
+            }
+            continue;
+        }
+        if args.validate {
+            println!("{} is a valid map", map_name);
+            continue;
+        }
+        let grid_info = GridInfo::new(map).expect("the map has no error");
+        solve_map(&args, &map_name, grid_info, search_mode, display_enabled, &color_mapping)?;
+    }
+    if nb_wrong_maps > 0 {
+        std::process::exit(1);
     }
     Ok(())
 }
+
+/// Save or solve a valid map and print the result
+fn solve_map(
+    args: &Args,
+    map_name: &str,
+    grid_info: GridInfo,
+    search_mode: SearchMode,
+    display_enabled: bool,
+    color_mapping: &ColorMapping,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if let Some(map_file) = &args.save_map {
+        if Image::is_image_file(map_file) {
+            std::fs::write(map_file, image_bytes(map_file, &Image::from_grid_info(&grid_info, &[], color_mapping)))?;
+        } else if map_file.ends_with(".map") {
+            let map_name = std::path::Path::new(map_file).file_name().map_or(map_file.clone(), |name| name.to_string_lossy().to_string());
+            std::fs::write(map_file, moving_ai::to_moving_ai_map(&grid_info))?;
+            std::fs::write(format!("{}.scen", map_file), moving_ai::to_moving_ai_scen(&grid_info, &map_name))?;
+        } else {
+            std::fs::write(map_file, grid_info.to_map_string())?;
+        }
+        return Ok(());
+    }
+    let mut sender = None;
+    let mut handle = None;
+    if display_enabled {
+        let (display_sender, receiver) = mpsc::channel::<(usize, usize)>();
+        let grid_info_copy_for_display = grid_info.clone();
+
+        let display = DisplayGrid{grid_info : grid_info_copy_for_display, receiver : Some(receiver)};
+        handle = Some(thread::spawn(move || {
+                display.display_grid();
+            }));
+        sender = Some(display_sender);
+    }
+    let mut wazelentin = PathDetective::new(grid_info, sender);
+    wazelentin.search_mode = search_mode;
+    wazelentin.metric = args.metric;
+    wazelentin.connectivity = args.connectivity;
+    let start_positions = wazelentin.grid_info.start_positions.clone();
+    let solve_start = Instant::now();
+    let path_results = if start_positions.len() > 1 {
+        wazelentin.find_and_transmit_paths()
+    } else {
+        vec![wazelentin.find_and_transmit_path()]
+    };
+    let elapsed = solve_start.elapsed();
+    for (start_pos, _) in start_positions.iter().zip(&path_results).filter(|(_, path_result)| path_result.is_none()) {
+        eprintln!("There is no available way for the exit case from {:?}", start_pos);
+    }
+    let any_angle = wazelentin.search_mode.pathfinder().is_any_angle();
+    if let Some(handle) = handle {
+        handle.join().unwrap();
+    }
+    if let Some(image_file) = &args.save_image {
+        let paths: Vec<_> = path_results
+            .iter()
+            .flatten()
+            .map(|path_result| if any_angle { theta_star::cases_of_waypoints(&path_result.path) } else { path_result.path.clone() })
+            .collect();
+        let image = Image::from_grid_info(&wazelentin.grid_info, &paths, color_mapping);
+        std::fs::write(image_file, image_bytes(image_file, &image))?;
+    }
+    if args.format == OutputFormat::Json {
+        // one JSON document per line and per map
+        let path_reports = start_positions
+            .iter()
+            .zip(path_results)
+            .map(|(start_pos, path_result)| PathReport::new(*start_pos, path_result))
+            .collect();
+        let solve_report = SolveReport::new(
+            map_name,
+            wazelentin.search_mode,
+            wazelentin.expanded_cases(),
+            elapsed,
+            path_reports,
+        );
+        println!("{}", solve_report.to_json());
+        return Ok(());
+    }
+    for (start_pos, path_result) in start_positions.iter().zip(path_results) {
+        if args.no_display {
+            // only the path, one line per start (empty when there is no way to an exit)
+            println!("{:?}", path_result.map_or(vec![], |path_result| path_result.path));
+            continue;
+        }
+        match path_result {
+            Some(path_result) => println!(
+                "Start {:?}: path of {} {} to the exit {:?}, length {:.3}, total cost {:.3}",
+                start_pos,
+                path_result.path.len(),
+                if any_angle { "waypoints" } else { "cases" },
+                path_result.exit_pos,
+                path_result.length,
+                path_result.cost
+            ),
+            None => println!("Start {:?}: no available way to an exit", start_pos),
+        }
+    }
+    Ok(())
+}
+
+/// PNG for a file ending with .png, PPM otherwise
+fn image_bytes(image_file: &str, image: &Image) -> Vec<u8> {
+    if image_file.to_ascii_lowercase().ends_with(".png") {
+        image.to_png()
+    } else {
+        image.to_ppm()
+    }
+}
@@ -0,0 +1,356 @@
+use crate::path_detective::{Connectivity, Metric, PathDetective, SearchMode};
+use crate::GridInfo;
+use std::collections::BTreeSet;
+use std::fmt;
+
+/// Problem found in a MovingAI `.map` or `.scen` file, lines start by 1
+#[derive(PartialEq, Debug)]
+pub enum Error {
+    /// `type octile`, `height H`, `width W` and `map` lines expected
+    WrongHeader { line: usize },
+    /// the number of rows or the width of a row does not match the header
+    IncoherenceGridSize { line: usize },
+    UnothorizedValue { line: usize, column: usize, found: char },
+    /// `bucket map width height start_x start_y goal_x goal_y optimal_length` expected
+    WrongScenarioLine { line: usize },
+    /// size of the scenario different from the map, or start / goal out of the map or blocked
+    IncoherenceScenarioWithMap { line: usize },
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::WrongHeader { line } => write!(f, "line {}: expected the `type octile`, `height`, `width` and `map` lines", line),
+            Error::IncoherenceGridSize { line } => write!(f, "line {}: the grid size does not match `height` and `width`", line),
+            Error::UnothorizedValue { line, column, found } => {
+                write!(f, "line {}, column {}: unauthorized value '{}', expected one of '.', 'G', 'S', '@', 'O', 'T' or 'W'", line, column, found)
+            }
+            Error::WrongScenarioLine { line } => write!(
+                f,
+                "line {}: expected `bucket map width height start_x start_y goal_x goal_y optimal_length`",
+                line
+            ),
+            Error::IncoherenceScenarioWithMap { line } => {
+                write!(f, "line {}: the scenario does not match the map (size, or start / goal out of the map or blocked)", line)
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// Grid of a MovingAI benchmark map, with the cases of `GridInfo` (`grid[0]` is the bottom row)
+#[derive(Clone, Debug, PartialEq)]
+pub struct MovingAiMap {
+    pub height: usize,
+    pub width: usize,
+    pub grid: Vec<Vec<char>>,
+}
+
+/// Start / goal pair of a `.scen` file, in the (row, column) coordinates of `GridInfo`
+#[derive(Clone, Debug, PartialEq)]
+pub struct Scenario {
+    pub bucket: usize,
+    pub map_name: String,
+    pub start: (usize, usize),
+    pub goal: (usize, usize),
+    pub optimal_length: f64,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ScenarioResult {
+    pub scenario: Scenario,
+    /// None when the goal cannot be reached
+    pub length: Option<f64>,
+}
+
+impl ScenarioResult {
+    /// the lengths of the `.scen` files are rounded to 8 decimals
+    const LENGTH_TOLERANCE: f64 = 1e-4;
+
+    pub fn is_optimal(&self) -> bool {
+        self.length
+            .is_some_and(|length| (length - self.scenario.optimal_length).abs() < ScenarioResult::LENGTH_TOLERANCE)
+    }
+}
+
+impl MovingAiMap {
+    /// MovingAI moves are the 8 moves without cutting corners, a diagonal move has a length of √2
+    pub const METRIC: Metric = Metric::Octile;
+    pub const CONNECTIVITY: Connectivity = Connectivity::MooreNoCornerCutting;
+
+    /// '.' and 'G' are ground and 'S' is swamp, all of them can be crossed.
+    /// '@' and 'O' are out of bounds, 'T' are trees and 'W' is water, all of them are blocked
+    fn case(symbol: char) -> Option<char> {
+        match symbol {
+            '.' | 'G' | 'S' => Some(GridInfo::CASE_OPEN),
+            '@' | 'O' | 'T' | 'W' => Some(GridInfo::CASE_CLOSE),
+            _ => None,
+        }
+    }
+
+    fn read_header_value(line: Option<(usize, &str)>, key: &str) -> Result<usize, Error> {
+        let (line_number, line) = line.ok_or(Error::WrongHeader { line: 1 })?;
+        line.strip_prefix(key)
+            .and_then(|value| value.trim().parse::<usize>().ok())
+            .ok_or(Error::WrongHeader { line: line_number })
+    }
+
+    /// Read a `.map` file: `type octile`, `height H`, `width W`, `map`, then the H rows from the top
+    pub fn new(map_file: &str) -> Result<MovingAiMap, Error> {
+        let mut lines = map_file.lines().enumerate().map(|(line_index, line)| (line_index + 1, line));
+        match lines.next() {
+            Some((_, "type octile")) => {}
+            other => return Err(Error::WrongHeader { line: other.map_or(1, |(line_number, _)| line_number) }),
+        }
+        let height = MovingAiMap::read_header_value(lines.next(), "height ")?;
+        let width = MovingAiMap::read_header_value(lines.next(), "width ")?;
+        match lines.next() {
+            Some((_, "map")) => {}
+            other => return Err(Error::WrongHeader { line: other.map_or(4, |(line_number, _)| line_number) }),
+        }
+
+        let mut grid: Vec<Vec<char>> = vec![];
+        for (line_number, line) in lines.by_ref().take(height) {
+            let mut grid_line = vec![];
+            for (column, symbol) in line.chars().enumerate() {
+                let case = MovingAiMap::case(symbol).ok_or(Error::UnothorizedValue {
+                    line: line_number,
+                    column: column + 1,
+                    found: symbol,
+                })?;
+                grid_line.push(case);
+            }
+            if grid_line.len() != width {
+                return Err(Error::IncoherenceGridSize { line: line_number });
+            }
+            grid.insert(0, grid_line);
+        }
+        // the grid starts on line 5, a missing row is reported where the file ends
+        if grid.len() != height {
+            return Err(Error::IncoherenceGridSize { line: grid.len() + 5 });
+        }
+        // a few maps end with an empty line
+        if let Some((line_number, _)) = lines.find(|(_, line)| !line.is_empty()) {
+            return Err(Error::IncoherenceGridSize { line: line_number });
+        }
+        Ok(MovingAiMap { height, width, grid })
+    }
+
+    /// (row, column) of `GridInfo` from the (x, y) of MovingAI, whose y goes down from the top row
+    fn pos(&self, x: usize, y: usize) -> Option<(usize, usize)> {
+        (x < self.width && y < self.height).then(|| (self.height - 1 - y, x))
+    }
+
+    fn is_open(&self, pos: (usize, usize)) -> bool {
+        self.grid[pos.0][pos.1] == GridInfo::CASE_OPEN
+    }
+
+    /// Read the `.scen` file of the map, the `version` line is optional
+    pub fn read_scenarios(&self, scen_file: &str) -> Result<Vec<Scenario>, Error> {
+        let mut scenarios = vec![];
+        for (line_index, line) in scen_file.lines().enumerate() {
+            let line_number = line_index + 1;
+            if line.starts_with("version") || line.trim().is_empty() {
+                continue;
+            }
+            let fields: Vec<_> = line.split_whitespace().collect();
+            if fields.len() != 9 {
+                return Err(Error::WrongScenarioLine { line: line_number });
+            }
+            let mut numbers = [0; 6];
+            for (number, field) in numbers.iter_mut().zip(&fields[2..8]) {
+                *number = field.parse::<usize>().map_err(|_| Error::WrongScenarioLine { line: line_number })?;
+            }
+            let bucket = fields[0].parse::<usize>().map_err(|_| Error::WrongScenarioLine { line: line_number })?;
+            let optimal_length = fields[8].parse::<f64>().map_err(|_| Error::WrongScenarioLine { line: line_number })?;
+            let [width, height, start_x, start_y, goal_x, goal_y] = numbers;
+            let incoherence = Error::IncoherenceScenarioWithMap { line: line_number };
+            if (width, height) != (self.width, self.height) {
+                return Err(incoherence);
+            }
+            let start = self.pos(start_x, start_y).filter(|&start| self.is_open(start)).ok_or(incoherence)?;
+            let goal = self
+                .pos(goal_x, goal_y)
+                .filter(|&goal| self.is_open(goal))
+                .ok_or(Error::IncoherenceScenarioWithMap { line: line_number })?;
+            scenarios.push(Scenario {
+                bucket,
+                map_name: fields[1].to_string(),
+                start,
+                goal,
+                optimal_length,
+            });
+        }
+        Ok(scenarios)
+    }
+
+    /// Grid with the goal of the scenario as its only exit
+    pub fn grid_info(&self, scenario: &Scenario) -> GridInfo {
+        let mut grid = self.grid.clone();
+        grid[scenario.goal.0][scenario.goal.1] = GridInfo::CASE_WIN;
+        GridInfo {
+            start_positions: vec![scenario.start],
+            exit_positions: BTreeSet::from([scenario.goal]),
+            row_max: self.height - 1,
+            column_max: self.width - 1,
+            grid,
+            metadata: vec![("name".to_string(), scenario.map_name.clone())],
+            legend: Default::default(),
+        }
+    }
+
+    /// Solve every scenario with the MovingAI moves.
+    /// BFS ignores the metric, use A* or Dijkstra to find the optimal lengths
+    pub fn run_scenarios(&self, scenarios: &[Scenario], search_mode: SearchMode) -> Vec<ScenarioResult> {
+        scenarios
+            .iter()
+            .map(|scenario| {
+                let mut wazelentin = PathDetective::new(self.grid_info(scenario), None);
+                wazelentin.search_mode = search_mode;
+                wazelentin.metric = MovingAiMap::METRIC;
+                wazelentin.connectivity = MovingAiMap::CONNECTIVITY;
+                ScenarioResult {
+                    scenario: scenario.clone(),
+                    length: wazelentin.find_and_transmit_path().map(|path_result| path_result.length),
+                }
+            })
+            .collect()
+    }
+}
+
+/// `.map` file of a grid: every case which can be crossed is written '.', weights included, and the blocked ones '@'
+pub fn to_moving_ai_map(grid_info: &GridInfo) -> String {
+    let mut map_file = format!("type octile\nheight {}\nwidth {}\nmap\n", grid_info.row_max + 1, grid_info.column_max + 1);
+    for grid_line in grid_info.grid.iter().rev() {
+        let symbols: String = grid_line
+            .iter()
+            .map(|&case| if GridInfo::case_cost(case).is_some() { '.' } else { '@' })
+            .collect();
+        map_file.push_str(&symbols);
+        map_file.push('\n');
+    }
+    map_file
+}
+
+/// `.scen` file of a grid: one line per start toward its nearest exit with the MovingAI moves,
+/// the starts which cannot reach an exit are left out
+pub fn to_moving_ai_scen(grid_info: &GridInfo, map_name: &str) -> String {
+    // the weights are lost in the `.map` file, the lengths are those of the grid without them
+    let mut unweighted_grid_info = grid_info.clone();
+    for case in unweighted_grid_info.grid.iter_mut().flatten() {
+        if *case != GridInfo::CASE_WIN && GridInfo::case_cost(*case).is_some() {
+            *case = GridInfo::CASE_OPEN;
+        }
+    }
+    let mut wazelentin = PathDetective::new(unweighted_grid_info, None);
+    wazelentin.search_mode = SearchMode::Dijkstra;
+    wazelentin.metric = MovingAiMap::METRIC;
+    wazelentin.connectivity = MovingAiMap::CONNECTIVITY;
+
+    let (height, width) = (grid_info.row_max + 1, grid_info.column_max + 1);
+    let mut scen_file = String::from("version 1\n");
+    for (start_pos, path_result) in grid_info
+        .start_positions
+        .iter()
+        .zip(wazelentin.find_and_transmit_paths_from_every_start())
+    {
+        if let Some(path_result) = path_result {
+            scen_file.push_str(&format!(
+                "{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{:.8}\n",
+                // MovingAI buckets group the scenarios by length / 4
+                (path_result.length / 4.0) as usize,
+                map_name,
+                width,
+                height,
+                start_pos.1,
+                height - 1 - start_pos.0,
+                path_result.exit_pos.1,
+                height - 1 - path_result.exit_pos.0,
+                path_result.length
+            ));
+        }
+    }
+    scen_file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::path_detective::Heuristic;
+
+    fn fixture(file_name: &str) -> String {
+        std::fs::read_to_string(format!("{}/data/moving_ai/{}", env!("CARGO_MANIFEST_DIR"), file_name)).unwrap()
+    }
+
+    #[test]
+    fn test_should_read_a_map_with_the_top_row_last_in_the_grid() {
+        let moving_ai_map = MovingAiMap::new("type octile\nheight 2\nwidth 3\nmap\n.T@\nGSW\n").unwrap();
+        assert_eq!((2, 3), (moving_ai_map.height, moving_ai_map.width));
+        assert_eq!(vec![vec!['O', 'O', 'E'], vec!['O', 'E', 'E']], moving_ai_map.grid);
+    }
+    #[test]
+    fn test_should_return_error_for_wrong_map() {
+        assert_eq!(Err(Error::WrongHeader { line: 1 }), MovingAiMap::new("type tile\nheight 2\nwidth 3\nmap\n"));
+        assert_eq!(Err(Error::WrongHeader { line: 3 }), MovingAiMap::new("type octile\nheight 2\nwidth x\nmap\n"));
+        assert_eq!(
+            Err(Error::UnothorizedValue { line: 6, column: 2, found: 'X' }),
+            MovingAiMap::new("type octile\nheight 2\nwidth 3\nmap\n...\n.X.\n")
+        );
+        assert_eq!(Err(Error::IncoherenceGridSize { line: 6 }), MovingAiMap::new("type octile\nheight 2\nwidth 3\nmap\n...\n..\n"));
+        assert_eq!(Err(Error::IncoherenceGridSize { line: 6 }), MovingAiMap::new("type octile\nheight 2\nwidth 3\nmap\n...\n"));
+        assert_eq!(Err(Error::IncoherenceGridSize { line: 7 }), MovingAiMap::new("type octile\nheight 1\nwidth 3\nmap\n...\n\n...\n"));
+        assert!(MovingAiMap::new("type octile\nheight 1\nwidth 3\nmap\n...\n\n").is_ok());
+    }
+    #[test]
+    fn test_should_read_scenarios_with_y_going_down() {
+        let moving_ai_map = MovingAiMap::new("type octile\nheight 2\nwidth 3\nmap\n.T@\nGSW\n").unwrap();
+        let scenarios = moving_ai_map.read_scenarios("version 1\n0\tsmall.map\t3\t2\t0\t0\t1\t1\t1.5\n").unwrap();
+        assert_eq!(
+            vec![Scenario {
+                bucket: 0,
+                map_name: "small.map".to_string(),
+                start: (1, 0),
+                goal: (0, 1),
+                optimal_length: 1.5,
+            }],
+            scenarios
+        );
+        assert_eq!(
+            Err(Error::IncoherenceScenarioWithMap { line: 2 }),
+            moving_ai_map.read_scenarios("version 1\n0\tsmall.map\t3\t2\t0\t0\t1\t0\t1\n")
+        );
+        assert_eq!(Err(Error::WrongScenarioLine { line: 1 }), moving_ai_map.read_scenarios("0\tsmall.map\t3\t2\t0\t0\t1\n"));
+    }
+    #[test]
+    fn test_every_fixture_scenario_should_be_solved_at_the_optimal_length() {
+        let moving_ai_map = MovingAiMap::new(&fixture("rooms.map")).unwrap();
+        let scenarios = moving_ai_map.read_scenarios(&fixture("rooms.map.scen")).unwrap();
+        assert!(!scenarios.is_empty());
+        for search_mode in [SearchMode::Dijkstra, SearchMode::AStar(Heuristic::Octile)] {
+            for scenario_result in moving_ai_map.run_scenarios(&scenarios, search_mode) {
+                assert!(scenario_result.is_optimal(), "{:?} {:?}", search_mode, scenario_result);
+            }
+        }
+        // BFS takes the fewest moves, which may be longer with the octile metric
+        for scenario_result in moving_ai_map.run_scenarios(&scenarios, SearchMode::Bfs) {
+            let length = scenario_result.length.unwrap();
+            assert!(length > scenario_result.scenario.optimal_length - ScenarioResult::LENGTH_TOLERANCE, "{:?}", scenario_result);
+            if scenario_result.scenario.start == scenario_result.scenario.goal {
+                assert!(scenario_result.is_optimal());
+            }
+        }
+    }
+    #[test]
+    fn test_exported_map_and_scenarios_should_be_read_back() {
+        let grid_info = GridInfo::new("2 6\n0 2\n1 6\nOX9EEEE\nOOOOOEO\nEOOEEEE").unwrap();
+        let map_file = to_moving_ai_map(&grid_info);
+        assert_eq!("type octile\nheight 3\nwidth 7\nmap\n...@@@@\n.....@.\n@..@@@@\n", map_file);
+        let moving_ai_map = MovingAiMap::new(&map_file).unwrap();
+        let scen_file = to_moving_ai_scen(&grid_info, "exported.map");
+        // the start (1, 6) is walled in
+        assert_eq!("version 1\n0\texported.map\t7\t3\t2\t2\t1\t0\t2.41421356\n", scen_file);
+        let scenarios = moving_ai_map.read_scenarios(&scen_file).unwrap();
+        assert!(moving_ai_map.run_scenarios(&scenarios, SearchMode::Dijkstra)[0].is_optimal());
+    }
+}
@@ -1,191 +1,318 @@
+use crate::bidirectional_bfs::BidirectionalBfs;
+use crate::jump_point_search::JumpPointSearch;
+use crate::theta_star::{self, ThetaStar};
+use crate::pathfinder::{AStar, Bfs, Dijkstra, OpenCase, Pathfinder, SearchGrid};
 use crate::GridInfo;
-use std::cell::RefCell;
-use std::collections::{HashMap, HashSet, VecDeque};
-use std::rc::Rc;
+use std::cell::Cell;
+use std::collections::{BinaryHeap, HashSet};
 use std::sync::mpsc;
 
-pub struct PathDetective {
-    pub grid_info: GridInfo,
-    pub sender : Option<mpsc::Sender<(usize, usize)>>
+/// Estimation of the remaining distance used by the A* search
+#[derive(Clone, Copy, Debug, PartialEq, Eq, clap::ValueEnum)]
+pub enum Heuristic {
+    /// max(|dr|, |dc|), exact when a diagonal move costs the same as a straight one
+    Chebyshev,
+    /// max(|dr|, |dc|) + (√2 - 1) * min(|dr|, |dc|), exact when a diagonal move costs √2
+    Octile,
+    /// |dr| + |dc|, exact when only the 4 straight moves are allowed
+    Manhattan,
+}
+
+impl Heuristic {
+    pub fn distance(&self, from: (usize, usize), to: (usize, usize)) -> f64 {
+        let row_delta = from.0.abs_diff(to.0) as f64;
+        let column_delta = from.1.abs_diff(to.1) as f64;
+        match self {
+            Heuristic::Chebyshev => row_delta.max(column_delta),
+            Heuristic::Octile => {
+                row_delta.max(column_delta) + (std::f64::consts::SQRT_2 - 1.0) * row_delta.min(column_delta)
+            }
+            Heuristic::Manhattan => row_delta + column_delta,
+        }
+    }
 }
 
-#[derive(Clone, Debug)]
-struct GraphInfo {
-    pos: (usize, usize),
-    distance_from_start: usize,
-    connected_case: Vec<(usize, usize)>,
+/// Length of a move, used to compute the length and the cost of a path
+#[derive(Clone, Copy, Debug, PartialEq, Eq, clap::ValueEnum)]
+pub enum Metric {
+    /// every move has a length of 1, diagonal ones included
+    Chebyshev,
+    /// straight moves have a length of 1 and diagonal moves a length of √2
+    Octile,
+}
+
+impl Metric {
+    pub fn move_length(&self, from: (usize, usize), to: (usize, usize)) -> f64 {
+        match self {
+            Metric::Octile if from.0 != to.0 && from.1 != to.1 => std::f64::consts::SQRT_2,
+            _ => 1.0,
+        }
+    }
+}
+
+/// Moves allowed from a case
+#[derive(Clone, Copy, Debug, PartialEq, Eq, clap::ValueEnum)]
+pub enum Connectivity {
+    /// the 4 straight moves
+    VonNeumann,
+    /// the 4 straight moves and the 4 diagonal moves
+    Moore,
+    /// the 8 moves of Moore, but a diagonal move is only allowed when both cases it goes along are open,
+    /// so a path can neither cut the corner of a blocked case nor squeeze between two of them
+    MooreNoCornerCutting,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SearchMode {
+    /// explore layer by layer from the start and stop at the nearest exit
+    /// (every move counts as 1, the weight of the cases and the metric are ignored)
+    Bfs,
+    /// explore toward the exit first and stop as soon as it is reached
+    /// (the path is only guaranteed to be the shortest with an heuristic which never overestimates,
+    /// i.e. Chebyshev with the Chebyshev metric, Chebyshev or Octile with the Octile metric)
+    AStar(Heuristic),
+    /// explore by increasing cost from the start, returns the cheapest path on weighted cases
+    Dijkstra,
+    /// Jump Point Search, an A* which only expands the cases where a path turns
+    /// (uniform-cost Moore grids only, other grids are searched with A*)
+    Jps,
+    /// breadth first search from the start and from the exits at the same time, until they meet
+    /// (every move counts as 1 as with Bfs)
+    BidirectionalBfs,
+    /// any-angle paths: waypoints in line of sight of each other and their Euclidean length
+    /// (the weight of the cases and the metric are ignored)
+    ThetaStar,
+}
+
+impl SearchMode {
+    /// names of the search modes, as given to `from_name`
+    pub const NAMES: [&'static str; 6] = ["bfs", "astar", "dijkstra", "jps", "bidirectional", "theta"];
+
+    /// Search mode of a name of `NAMES`, the heuristic is only followed by the A* search
+    pub fn from_name(name: &str, heuristic: Heuristic) -> Option<SearchMode> {
+        match name {
+            "bfs" => Some(SearchMode::Bfs),
+            "astar" => Some(SearchMode::AStar(heuristic)),
+            "dijkstra" => Some(SearchMode::Dijkstra),
+            "jps" => Some(SearchMode::Jps),
+            "bidirectional" => Some(SearchMode::BidirectionalBfs),
+            "theta" => Some(SearchMode::ThetaStar),
+            _ => None,
+        }
+    }
+
+    /// Algorithm of the search mode
+    pub fn pathfinder(&self) -> Box<dyn Pathfinder> {
+        match *self {
+            SearchMode::Bfs => Box::new(Bfs),
+            SearchMode::AStar(heuristic) => Box::new(AStar(heuristic)),
+            SearchMode::Dijkstra => Box::new(Dijkstra),
+            SearchMode::Jps => Box::new(JumpPointSearch),
+            SearchMode::BidirectionalBfs => Box::new(BidirectionalBfs),
+            SearchMode::ThetaStar => Box::new(ThetaStar),
+        }
+    }
+}
+
+/// Path found from the start to the nearest reachable exit (both included)
+#[derive(Clone, Debug, PartialEq)]
+pub struct PathResult {
+    /// connected cases, or waypoints in line of sight of each other for an any-angle search
+    pub path: Vec<(usize, usize)>,
+    /// exit chosen among the exits of the grid
+    pub exit_pos: (usize, usize),
+    /// sum of the length of each move with the metric of the search
+    pub length: f64,
+    /// sum of the length of each move multiplied by the cost of the case stepped on,
+    /// the length itself for an any-angle search which crosses the weighted cases as open ones
+    pub cost: f64,
+}
+
+/// Solver of a grid: the search, the metric and the connectivity can be changed before solving.
+/// The cases of the path found are sent to `sender` from the exit to the start, to draw it with `DisplayGrid`
+pub struct PathDetective {
+    pub grid_info: GridInfo,
+    pub sender : Option<mpsc::Sender<(usize, usize)>>,
+    pub search_mode: SearchMode,
+    pub metric: Metric,
+    pub connectivity: Connectivity,
+    expanded_cases: Cell<usize>,
 }
 
 impl PathDetective {
-    fn is_valid_case(&self, row: usize, column: usize) -> bool {
-        if let Some(case) = self.grid_info.grid.get(row).and_then(|r| r.get(column)) {
-            *case != GridInfo::CASE_CLOSE
-        } else {
-            false
+    pub fn new(grid_info: GridInfo, sender: Option<mpsc::Sender<(usize, usize)>>) -> PathDetective {
+        PathDetective {
+            grid_info,
+            sender,
+            search_mode: SearchMode::Bfs,
+            metric: Metric::Chebyshev,
+            connectivity: Connectivity::Moore,
+            expanded_cases: Cell::new(0),
         }
     }
-    fn fill_graph(&self, graph_cases: &mut HashMap<(usize, usize), Rc<RefCell<GraphInfo>>>) {
-        let mut graph_case = GraphInfo {
-            pos: (usize::MAX, usize::MAX),
-            distance_from_start: usize::MAX,
-            connected_case: Vec::new(),
-        };
-        let mut row: usize = 0;
-        let directions = [
-            (-1, 0),  /* DOWN */
-            (-1, 1),  /* DOWN RIGHT */
-            (0, 1),   /* RIGHT */
-            (1, 1),   /* UP RIGHT */
-            (1, 0),   /* UP */
-            (1, -1),  /* UP LEFT */
-            (0, -1),  /* LEFT */
-            (-1, -1), /* DOWN LEFT */
-        ];
-
-        for grid_line in &self.grid_info.grid {
-            let mut column = 0;
-            for _ in grid_line {
-                if self.grid_info.grid[row][column] != GridInfo::CASE_CLOSE {
-                    graph_case.connected_case.clear();
-                    graph_case.pos = (row, column);
-
-                    for &(row_offset, column_offset) in &directions {
-                        if row_offset < 0 && row == 0 {
-                            continue;
-                        }
-                        if column_offset < 0 && column == 0 {
-                            continue;
-                        }
-                        let new_row = (row as isize + row_offset) as usize;
-                        let new_column = (column as isize + column_offset) as usize;
-                        if self.is_valid_case(new_row, new_column) {
-                            graph_case.connected_case.push((new_row, new_column));
-                        }
-                    }
-                    graph_case.distance_from_start = usize::MAX;
-                    let graph_info_rc = Rc::new(RefCell::new(graph_case.clone()));
-                    graph_cases.insert((row, column), graph_info_rc);
-                }
-                column += 1;
+
+    /// Number of cases whose neighbours were explored by the last search
+    pub fn expanded_cases(&self) -> usize {
+        self.expanded_cases.get()
+    }
+
+    /// Graph of the grid with the metric and the connectivity of the searches
+    pub fn search_grid(&self) -> SearchGrid<'_> {
+        SearchGrid::new(&self.grid_info, self.metric, self.connectivity)
+    }
+
+    /// Start used by the single path searches, the first one of the grid
+    fn start_pos(&self) -> (usize, usize) {
+        self.grid_info.start_positions[0]
+    }
+
+    /// Send the path to the display from the exit to the start
+    fn transmit_path(&self, shortest_path: &[(usize, usize)]) {
+        if let Some(sender) = &self.sender {
+            for pos in shortest_path.iter().rev().skip(1) {
+                sender.send(*pos).unwrap();
             }
-            row += 1;
         }
-        self.fill_intersection_distance(graph_cases);
-    }
-
-    fn fill_intersection_distance(&self, graph_cases: &HashMap<(usize, usize), Rc<RefCell<GraphInfo>>>) {
-        if graph_cases.get(&self.grid_info.start_pos).is_some() {
-            let mut visited_positions: HashSet<(usize, usize)> = HashSet::new();
-            let mut queue: VecDeque<((usize, usize), usize)> = VecDeque::new();
-
-            queue.push_back((self.grid_info.start_pos, 0));
-
-            while let Some((current_pos, distance)) = queue.pop_front() {
-                //println!("POPPING {:?}", current_pos);
-                visited_positions.insert(current_pos);
-
-                let rc_current_info = graph_cases
-                    .get(&current_pos)
-                    .expect("Position not found in graph_cases");
-                let mut current_info = rc_current_info.borrow_mut();
-                current_info.distance_from_start = distance;
-
-                /*println!(
-                    "Current pos : {:?} | associated {:?} | dist {:?}",
-                    current_pos, current_info.connected_case, distance
-                );*/
-                if current_info
-                    .connected_case
-                    .contains(&self.grid_info.exit_pos)
-                {
-                    /*println!(
-                        "Found target value {:?} in connected_case for position {:?}",
-                        &self.grid_info.exit_pos, current_pos
-                    );*/
-                    break;
-                }
+    }
 
-                for &connected_pos in &current_info.connected_case {
-                    //println!("{:?}", connected_pos);
-                    if !visited_positions.contains(&connected_pos) {
-                        let contains_value = queue
-                            .iter()
-                            .any(|(first_value, _)| first_value == &connected_pos);
-                        if !contains_value {
-                            queue.push_back((connected_pos, distance + 1));
-                        }
-                    }
-                }
-            }
-        } else {
-            println!("Start position not found in graph_cases");
+    /// Tell the display that every path has been sent
+    fn transmit_end(&self) {
+        if let Some(sender) = &self.sender {
+            sender.send((usize::MAX, usize::MAX)).unwrap();
         }
     }
 
-    #[allow(unused_must_use)]
-    fn find_best_intersection (&self, graph: &HashMap<(usize, usize), Rc<RefCell<GraphInfo>>>, visited: &mut HashSet<(usize, usize)>, pos: &(usize, usize),
-        shortest_path: &mut Vec<(usize, usize)>) -> bool {
-        if pos != &self.grid_info.start_pos {
-            let rc_graph_info = graph.get(pos).unwrap();  //.expect("Position not found in graph_cases");
-            let graph_info = rc_graph_info.borrow();
-
-            let mut next_case_to_check = ((usize::MAX, usize::MAX), usize::MAX);
-            visited.insert(*pos);
-            for connected_pos in &graph_info.connected_case {
-                if !visited.contains(connected_pos) {
-                    let rc_graph_info_for_connected_pos = graph
-                        .get(connected_pos)
-                        .expect("Position not found in graph_cases");
-                    let graph_info_for_connected_pos = rc_graph_info_for_connected_pos.borrow();
-                    if graph_info_for_connected_pos.distance_from_start < next_case_to_check.1 {
-                        next_case_to_check = (
-                            *connected_pos,
-                            graph_info_for_connected_pos.distance_from_start,
-                        );
-                    }
-                }
-            }
-            if let Some(sender) = &self.sender
-            {
-                sender.send(next_case_to_check.0).unwrap();
+    /// Search from `start_pos` with the algorithm and send the path found to the display
+    fn find_and_transmit_path_from(&self, pathfinder: &dyn Pathfinder, search_grid: &SearchGrid, start_pos: (usize, usize)) -> Option<PathResult> {
+        let path_result = pathfinder.find_path(search_grid, start_pos, &self.grid_info.exit_positions);
+        match &path_result {
+            // the display shows every case crossed between two waypoints
+            Some(path_result) if pathfinder.is_any_angle() => self.transmit_path(&theta_star::cases_of_waypoints(&path_result.path)),
+            Some(path_result) => self.transmit_path(&path_result.path),
+            None => {}
+        }
+        path_result
+    }
+
+    /// Path from the first start to its nearest exit with any algorithm, the search mode is not followed
+    pub fn find_and_transmit_path_with(&self, pathfinder: &dyn Pathfinder) -> Option<PathResult> {
+        let search_grid = self.search_grid();
+        let path_result = self.find_and_transmit_path_from(pathfinder, &search_grid, self.start_pos());
+        self.expanded_cases.set(search_grid.expanded_cases());
+        self.transmit_end();
+        path_result
+    }
+
+    pub fn find_and_transmit_path(&self) -> Option<PathResult> {
+        self.find_and_transmit_path_with(self.search_mode.pathfinder().as_ref())
+    }
+
+    /// Path from every start of the grid to its nearest exit with any algorithm, one search per start,
+    /// in the order of `start_positions` (None for a start which cannot reach any exit).
+    /// The expanded cases are those of all the searches
+    pub fn find_and_transmit_paths_with(&self, pathfinder: &dyn Pathfinder) -> Vec<Option<PathResult>> {
+        let search_grid = self.search_grid();
+        let path_results = self
+            .grid_info
+            .start_positions
+            .iter()
+            .map(|&start_pos| self.find_and_transmit_path_from(pathfinder, &search_grid, start_pos))
+            .collect();
+        self.expanded_cases.set(search_grid.expanded_cases());
+        self.transmit_end();
+        path_results
+    }
+
+    /// Path from every start of the grid with the search mode, in the order of `start_positions`.
+    /// Dijkstra searches all the starts in a single pass from the exits, the other modes once per start
+    pub fn find_and_transmit_paths(&self) -> Vec<Option<PathResult>> {
+        match self.search_mode {
+            SearchMode::Dijkstra => self.find_and_transmit_paths_from_every_start(),
+            search_mode => self.find_and_transmit_paths_with(search_mode.pathfinder().as_ref()),
+        }
+    }
+
+    /// Cheapest path from every start of the grid to its nearest exit, in the order of `start_positions`
+    /// (None for a start which cannot reach any exit).
+    /// One search is done from all the exits at once, whatever the search mode, following the metric and the cost of the cases.
+    pub fn find_and_transmit_paths_from_every_start(&self) -> Vec<Option<PathResult>> {
+        let search_grid = self.search_grid();
+        let mut costs: Vec<f64> = vec![f64::INFINITY; search_grid.case_count()];
+        // next case toward the nearest exit
+        let mut next_cases: Vec<usize> = vec![usize::MAX; search_grid.case_count()];
+        let mut open_cases: BinaryHeap<OpenCase> = BinaryHeap::new();
+        let mut remaining_starts: HashSet<(usize, usize)> = self.grid_info.start_positions.iter().copied().collect();
+
+        for &exit_pos in &self.grid_info.exit_positions {
+            costs[search_grid.index(exit_pos)] = 0.0;
+            open_cases.push(OpenCase {
+                estimated_cost: 0.0,
+                cost_from_start: 0.0,
+                pos: exit_pos,
+            });
+        }
+        while let Some(OpenCase { cost_from_start, pos, .. }) = open_cases.pop() {
+            if cost_from_start > costs[search_grid.index(pos)] {
+                continue;
             }
-            //print!("{:?}", next_case_to_check.0);
-            shortest_path.insert(0, next_case_to_check.0);
-            if next_case_to_check.0 == (usize::MAX, usize::MAX)
-            {
-                return false;
+            remaining_starts.remove(&pos);
+            if remaining_starts.is_empty() {
+                break;
             }
-            else {
-                self.find_best_intersection(&graph.clone(), visited, &next_case_to_check.0, shortest_path);
+            search_grid.count_expanded_case();
+            for connected_pos in search_grid.connected_cases(pos.0, pos.1) {
+                // the move goes from connected_pos to pos
+                let cost = cost_from_start + search_grid.move_cost(connected_pos, pos);
+                let connected_index = search_grid.index(connected_pos);
+                if cost < costs[connected_index] {
+                    costs[connected_index] = cost;
+                    next_cases[connected_index] = search_grid.index(pos);
+                    open_cases.push(OpenCase {
+                        estimated_cost: cost,
+                        cost_from_start: cost,
+                        pos: connected_pos,
+                    });
+                }
             }
         }
-        true
-    }
-
-    pub fn find_and_transmit_shortest_path(&self) -> Option<Vec<(usize, usize)>> {
-        let mut shortest_path = Vec::new();
-        let mut graph_cases: HashMap<(usize, usize), Rc<RefCell<GraphInfo>>> = HashMap::new();
-        self.fill_graph(&mut graph_cases);
-        let mut visited: std::collections::HashSet<(usize, usize)> =
-            std::collections::HashSet::new();
-        shortest_path.push(self.grid_info.exit_pos);
-        if self.find_best_intersection(&graph_cases.clone(), &mut visited, &self.grid_info.exit_pos, &mut shortest_path)
-        {
-            return Some(shortest_path);
-        }
-        eprintln!("There is no available way for the exit case");
-        None
+
+        let path_results = self
+            .grid_info
+            .start_positions
+            .iter()
+            .map(|&start_pos| {
+                let mut current_index = search_grid.index(start_pos);
+                if costs[current_index] == f64::INFINITY {
+                    return None;
+                }
+                let mut path = vec![start_pos];
+                while next_cases[current_index] != usize::MAX {
+                    current_index = next_cases[current_index];
+                    path.push(search_grid.pos(current_index));
+                }
+                self.transmit_path(&path);
+                Some(search_grid.path_result(path))
+            })
+            .collect();
+        self.expanded_cases.set(search_grid.expanded_cases());
+        self.transmit_end();
+        path_results
     }
+
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::legend::Legend;
+    use std::collections::BTreeSet;
     #[test]
     fn test_pos_for_second_case_on_first_row() {
         let grid_info = GridInfo {
-            start_pos: (0, 2),
-            exit_pos: (usize::MAX, usize::MAX),
+            start_positions: vec![(0, 2)],
+            exit_positions: BTreeSet::new(),
+            metadata: vec![],
+            legend: Legend::default(),
             row_max: 2,
             column_max: 6,
             grid: [
@@ -195,20 +322,20 @@ mod tests {
             ]
             .to_vec(),
         };
-        let wazelentin = PathDetective {
-            grid_info: grid_info,
-            sender: None
-        };
-        let mut graph_cases: HashMap<(usize, usize), Rc<RefCell<GraphInfo>>> = HashMap::new();
+        let wazelentin = PathDetective::new(grid_info, None);
 
-        wazelentin.fill_graph(&mut graph_cases);
-        assert_eq!(graph_cases.get(&(0, 2)).unwrap().borrow().pos, (0, 2));
+        assert_eq!(wazelentin.search_grid().index((0, 2)), 2);
+        assert_eq!(wazelentin.search_grid().index((2, 1)), 15);
+        assert_eq!(wazelentin.search_grid().pos(15), (2, 1));
+        assert_eq!(wazelentin.search_grid().case_count(), 21);
 }
     #[test]
     fn test_connected_case() {
         let mut grid_info = GridInfo {
-            start_pos: (0, 2),
-            exit_pos: (usize::MAX, usize::MAX),
+            start_positions: vec![(0, 2)],
+            exit_positions: BTreeSet::new(),
+            metadata: vec![],
+            legend: Legend::default(),
             row_max: 2,
             column_max: 6,
             grid: [
@@ -219,22 +346,19 @@ mod tests {
             .to_vec(),
         };
         grid_info.grid.reverse();
-        let wazelentin = PathDetective {
-            grid_info: grid_info,
-            sender: None
-        };
-        let mut graph_cases: HashMap<(usize, usize), Rc<RefCell<GraphInfo>>> = HashMap::new();
+        let wazelentin = PathDetective::new(grid_info, None);
 
-        wazelentin.fill_graph(&mut graph_cases);
-        assert_eq!(graph_cases.get(&(0, 0)).unwrap().borrow().connected_case, [(0, 1), (1, 1), (1, 0)]);
-        assert_eq!(graph_cases.get(&(1, 4)).unwrap().borrow().connected_case, [(1, 3)]);
+        assert_eq!(wazelentin.search_grid().connected_cases(0, 0).collect::<Vec<_>>(), [(0, 1), (1, 1), (1, 0)]);
+        assert_eq!(wazelentin.search_grid().connected_cases(1, 4).collect::<Vec<_>>(), [(1, 3)]);
     }
 
     #[test]
     fn test_find_best_path() {
         let mut grid_info = GridInfo {
-            start_pos: (2, 6),
-            exit_pos: (0, 0),
+            start_positions: vec![(2, 6)],
+            exit_positions: BTreeSet::from([(0, 0)]),
+            metadata: vec![],
+            legend: Legend::default(),
             row_max: 5,
             column_max: 6,
             grid: [
@@ -248,18 +372,17 @@ mod tests {
             .to_vec(),
         };
         grid_info.grid.reverse();
-        let wazelentin = PathDetective {
-            grid_info: grid_info,
-            sender: None
-        };
-        assert_eq!(wazelentin.find_and_transmit_shortest_path().unwrap(), [(2, 6), (3, 6), (4, 5), (3, 4), (2, 3), (1, 2), (1, 1), (0, 0)]);
+        let wazelentin = PathDetective::new(grid_info, None);
+        assert_eq!(wazelentin.find_and_transmit_path().unwrap().path, [(2, 6), (3, 6), (4, 5), (3, 4), (2, 3), (1, 2), (1, 1), (0, 0)]);
     }
 
     #[test]
     fn test_should_return_error_message_if_exit_case_is_unreachable() {
         let mut grid_info = GridInfo {
-            start_pos: (2, 6),
-            exit_pos: (0, 0),
+            start_positions: vec![(2, 6)],
+            exit_positions: BTreeSet::from([(0, 0)]),
+            metadata: vec![],
+            legend: Legend::default(),
             row_max: 5,
             column_max: 6,
             grid: [
@@ -273,10 +396,427 @@ mod tests {
             .to_vec(),
         };
         grid_info.grid.reverse();
-        let wazelentin = PathDetective {
-            grid_info: grid_info,
-            sender: None
+        let wazelentin = PathDetective::new(grid_info, None);
+        assert!(wazelentin.find_and_transmit_path().is_none());
+    }
+
+    fn map_with_two_ways() -> GridInfo {
+        let mut grid_info = GridInfo {
+            start_positions: vec![(2, 6)],
+            exit_positions: BTreeSet::from([(0, 0)]),
+            metadata: vec![],
+            legend: Legend::default(),
+            row_max: 5,
+            column_max: 6,
+            grid: [
+                ['E', 'O', 'E', 'O', 'E', 'O', 'E'].to_vec(),
+                ['O', 'O', 'O', 'O', 'O', 'O', 'O'].to_vec(),
+                ['O', 'O', 'O', 'O', 'O', 'E', 'O'].to_vec(),
+                ['O', 'O', 'O', 'O', 'O', 'E', 'O'].to_vec(),
+                ['O', 'O', 'O', 'O', 'O', 'E', 'E'].to_vec(),
+                ['X', 'O', 'O', 'E', 'E', 'O', 'O'].to_vec(),
+            ]
+            .to_vec(),
+        };
+        grid_info.grid.reverse();
+        grid_info
+    }
+
+    fn assert_is_a_continuous_path(path: &[(usize, usize)]) {
+        for step in path.windows(2) {
+            assert!(step[0].0.abs_diff(step[1].0) <= 1 && step[0].1.abs_diff(step[1].1) <= 1);
+        }
+    }
+
+    #[test]
+    fn test_heuristic_distance() {
+        assert_eq!(Heuristic::Chebyshev.distance((0, 0), (3, 5)), 5.0);
+        assert_eq!(Heuristic::Manhattan.distance((3, 5), (0, 0)), 8.0);
+        assert!((Heuristic::Octile.distance((0, 0), (3, 5)) - (2.0 + 3.0 * std::f64::consts::SQRT_2)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_find_best_path_with_astar_has_the_bfs_length() {
+        let bfs_path = PathDetective::new(map_with_two_ways(), None)
+            .find_and_transmit_path()
+            .unwrap()
+            .path;
+        let mut wazelentin = PathDetective::new(map_with_two_ways(), None);
+        wazelentin.search_mode = SearchMode::AStar(Heuristic::Chebyshev);
+        let astar_path = wazelentin.find_and_transmit_path().unwrap().path;
+        assert_eq!(bfs_path.len(), astar_path.len());
+        assert_eq!(astar_path.first(), Some(&(2, 6)));
+        assert_eq!(astar_path.last(), Some(&(0, 0)));
+        assert_is_a_continuous_path(&astar_path);
+    }
+
+    #[test]
+    fn test_find_path_with_every_heuristic() {
+        for heuristic in [Heuristic::Chebyshev, Heuristic::Octile, Heuristic::Manhattan] {
+            let mut wazelentin = PathDetective::new(map_with_two_ways(), None);
+            wazelentin.search_mode = SearchMode::AStar(heuristic);
+            let astar_path = wazelentin.find_and_transmit_path().unwrap().path;
+            assert_eq!(astar_path.first(), Some(&(2, 6)));
+            assert_eq!(astar_path.last(), Some(&(0, 0)));
+            assert_is_a_continuous_path(&astar_path);
+        }
+    }
+
+    #[test]
+    fn test_astar_should_return_none_if_exit_case_is_unreachable() {
+        let mut grid_info = map_with_two_ways();
+        grid_info.grid[1] = ['E', 'E', 'E', 'E', 'E', 'E', 'E'].to_vec();
+        let mut wazelentin = PathDetective::new(grid_info, None);
+        wazelentin.search_mode = SearchMode::AStar(Heuristic::Chebyshev);
+        assert!(wazelentin.find_and_transmit_path().is_none());
+    }
+
+    #[test]
+    fn test_astar_transmit_path_from_exit_to_start() {
+        let (sender, receiver) = mpsc::channel::<(usize, usize)>();
+        let mut wazelentin = PathDetective::new(map_with_two_ways(), Some(sender));
+        wazelentin.search_mode = SearchMode::AStar(Heuristic::Chebyshev);
+        let astar_path = wazelentin.find_and_transmit_path().unwrap().path;
+        drop(wazelentin);
+        let transmitted: Vec<(usize, usize)> = receiver.iter().collect();
+        let mut expected: Vec<(usize, usize)> = astar_path.iter().rev().skip(1).copied().collect();
+        expected.push((usize::MAX, usize::MAX));
+        assert_eq!(expected, transmitted);
+        assert_eq!(transmitted[transmitted.len() - 2], (2, 6));
+    }
+
+    fn map_with_mud() -> GridInfo {
+        let mut grid_info = GridInfo {
+            start_positions: vec![(0, 0)],
+            exit_positions: BTreeSet::from([(0, 4)]),
+            metadata: vec![],
+            legend: Legend::default(),
+            row_max: 2,
+            column_max: 4,
+            grid: [
+                ['O', 'O', 'O', 'O', 'O'].to_vec(),
+                ['O', '9', '9', '9', 'O'].to_vec(),
+                ['O', '9', '9', '9', 'X'].to_vec(),
+            ]
+            .to_vec(),
+        };
+        grid_info.grid.reverse();
+        grid_info
+    }
+
+    #[test]
+    fn test_dijkstra_goes_around_weighted_cases() {
+        let mut wazelentin = PathDetective::new(map_with_mud(), None);
+        wazelentin.search_mode = SearchMode::Dijkstra;
+        let path_result = wazelentin.find_and_transmit_path().unwrap();
+        assert_eq!(path_result.path, [(0, 0), (1, 0), (2, 1), (2, 2), (2, 3), (1, 4), (0, 4)]);
+        assert_eq!(path_result.cost, 6.0);
+    }
+
+    #[test]
+    fn test_astar_goes_around_weighted_cases() {
+        let mut wazelentin = PathDetective::new(map_with_mud(), None);
+        wazelentin.search_mode = SearchMode::AStar(Heuristic::Chebyshev);
+        let path_result = wazelentin.find_and_transmit_path().unwrap();
+        assert_eq!(path_result.cost, 6.0);
+        assert_is_a_continuous_path(&path_result.path);
+    }
+
+    #[test]
+    fn test_bfs_reports_the_cost_of_its_path_through_weighted_cases() {
+        let path_result = PathDetective::new(map_with_mud(), None).find_and_transmit_path().unwrap();
+        assert_eq!(path_result.path.len(), 5);
+        assert_eq!(path_result.cost, 28.0);
+    }
+
+    #[test]
+    fn test_dijkstra_cost_on_open_map_is_the_number_of_steps() {
+        let mut wazelentin = PathDetective::new(map_with_two_ways(), None);
+        wazelentin.search_mode = SearchMode::Dijkstra;
+        let path_result = wazelentin.find_and_transmit_path().unwrap();
+        assert_eq!(path_result.path.len(), 8);
+        assert_eq!(path_result.length, 7.0);
+        assert_eq!(path_result.cost, 7.0);
+    }
+
+    #[test]
+    fn test_move_length() {
+        assert_eq!(Metric::Chebyshev.move_length((1, 1), (2, 2)), 1.0);
+        assert_eq!(Metric::Octile.move_length((1, 1), (1, 2)), 1.0);
+        assert_eq!(Metric::Octile.move_length((1, 1), (0, 2)), std::f64::consts::SQRT_2);
+    }
+
+    #[test]
+    fn test_octile_metric_prefers_straight_moves() {
+        // both ways take 4 moves, but the top one has 2 diagonal moves and the bottom one 4
+        let mut grid_info = GridInfo {
+            start_positions: vec![(1, 0)],
+            exit_positions: BTreeSet::from([(1, 4)]),
+            metadata: vec![],
+            legend: Legend::default(),
+            row_max: 2,
+            column_max: 4,
+            grid: [
+                ['E', 'O', 'O', 'O', 'E'].to_vec(),
+                ['O', 'E', 'O', 'E', 'X'].to_vec(),
+                ['E', 'O', 'E', 'O', 'E'].to_vec(),
+            ]
+            .to_vec(),
+        };
+        grid_info.grid.reverse();
+        let mut wazelentin = PathDetective::new(grid_info.clone(), None);
+        wazelentin.search_mode = SearchMode::Dijkstra;
+        wazelentin.metric = Metric::Octile;
+        let path_result = wazelentin.find_and_transmit_path().unwrap();
+        assert_eq!(path_result.path, [(1, 0), (2, 1), (2, 2), (2, 3), (1, 4)]);
+        assert!((path_result.length - (2.0 + 2.0 * std::f64::consts::SQRT_2)).abs() < 1e-9);
+        assert_eq!(path_result.length, path_result.cost);
+
+        let mut wazelentin = PathDetective::new(grid_info, None);
+        wazelentin.search_mode = SearchMode::AStar(Heuristic::Octile);
+        wazelentin.metric = Metric::Octile;
+        let path_result = wazelentin.find_and_transmit_path().unwrap();
+        assert_eq!(path_result.path, [(1, 0), (2, 1), (2, 2), (2, 3), (1, 4)]);
+    }
+
+    #[test]
+    fn test_octile_metric_multiplies_the_case_cost() {
+        let mut wazelentin = PathDetective::new(map_with_mud(), None);
+        wazelentin.search_mode = SearchMode::Dijkstra;
+        wazelentin.metric = Metric::Octile;
+        let path_result = wazelentin.find_and_transmit_path().unwrap();
+        assert!((path_result.cost - (4.0 + 2.0 * std::f64::consts::SQRT_2)).abs() < 1e-9);
+    }
+
+    fn map_with_diagonal_gap() -> GridInfo {
+        let mut grid_info = GridInfo {
+            start_positions: vec![(0, 0)],
+            exit_positions: BTreeSet::from([(2, 2)]),
+            metadata: vec![],
+            legend: Legend::default(),
+            row_max: 2,
+            column_max: 2,
+            grid: [
+                ['O', 'O', 'X'].to_vec(),
+                ['O', 'O', 'E'].to_vec(),
+                ['O', 'E', 'O'].to_vec(),
+            ]
+            .to_vec(),
         };
-        assert_eq!(true, wazelentin.find_and_transmit_shortest_path().is_none());
+        grid_info.grid.reverse();
+        grid_info
+    }
+
+    #[test]
+    fn test_connected_case_for_each_connectivity() {
+        let mut wazelentin = PathDetective::new(map_with_diagonal_gap(), None);
+        assert_eq!(wazelentin.search_grid().connected_cases(1, 1).collect::<Vec<_>>(), [(0, 2), (2, 2), (2, 1), (2, 0), (1, 0), (0, 0)]);
+        wazelentin.connectivity = Connectivity::VonNeumann;
+        assert_eq!(wazelentin.search_grid().connected_cases(1, 1).collect::<Vec<_>>(), [(2, 1), (1, 0)]);
+        wazelentin.connectivity = Connectivity::MooreNoCornerCutting;
+        assert_eq!(wazelentin.search_grid().connected_cases(1, 1).collect::<Vec<_>>(), [(2, 1), (2, 0), (1, 0)]);
+    }
+
+    #[test]
+    fn test_no_corner_cutting_does_not_squeeze_between_blocked_cases() {
+        let mut grid_info = GridInfo {
+            start_positions: vec![(0, 1)],
+            exit_positions: BTreeSet::from([(1, 0)]),
+            metadata: vec![],
+            legend: Legend::default(),
+            row_max: 2,
+            column_max: 2,
+            grid: [
+                ['O', 'O', 'O'].to_vec(),
+                ['X', 'E', 'O'].to_vec(),
+                ['E', 'O', 'O'].to_vec(),
+            ]
+            .to_vec(),
+        };
+        grid_info.grid.reverse();
+        let path = PathDetective::new(grid_info.clone(), None).find_and_transmit_path().unwrap().path;
+        assert_eq!(path, [(0, 1), (1, 0)]);
+
+        let mut wazelentin = PathDetective::new(grid_info, None);
+        wazelentin.connectivity = Connectivity::MooreNoCornerCutting;
+        let path = wazelentin.find_and_transmit_path().unwrap().path;
+        assert_eq!(path, [(0, 1), (0, 2), (1, 2), (2, 2), (2, 1), (2, 0), (1, 0)]);
+    }
+
+    #[test]
+    fn test_von_neumann_path_with_manhattan_heuristic() {
+        let mut wazelentin = PathDetective::new(map_with_two_ways(), None);
+        wazelentin.connectivity = Connectivity::VonNeumann;
+        let bfs_path = wazelentin.find_and_transmit_path().unwrap().path;
+        wazelentin.search_mode = SearchMode::AStar(Heuristic::Manhattan);
+        let astar_path = wazelentin.find_and_transmit_path().unwrap().path;
+        assert_eq!(bfs_path.len(), astar_path.len());
+        for step in astar_path.windows(2) {
+            assert_eq!(step[0].0.abs_diff(step[1].0) + step[0].1.abs_diff(step[1].1), 1);
+        }
+    }
+
+    fn map_with_three_exits() -> GridInfo {
+        let mut grid_info = GridInfo {
+            start_positions: vec![(1, 2)],
+            exit_positions: BTreeSet::from([(2, 0), (0, 6), (2, 6)]),
+            metadata: vec![],
+            legend: Legend::default(),
+            row_max: 2,
+            column_max: 6,
+            grid: [
+                ['X', 'O', 'O', 'O', 'E', 'O', 'X'].to_vec(),
+                ['O', 'O', 'O', 'O', 'E', 'E', 'O'].to_vec(),
+                ['O', 'O', 'O', 'O', 'O', 'O', 'X'].to_vec(),
+            ]
+            .to_vec(),
+        };
+        grid_info.grid.reverse();
+        grid_info
+    }
+
+    #[test]
+    fn test_every_search_mode_goes_to_the_nearest_exit() {
+        for search_mode in [SearchMode::Bfs, SearchMode::Dijkstra, SearchMode::AStar(Heuristic::Chebyshev)] {
+            let mut wazelentin = PathDetective::new(map_with_three_exits(), None);
+            wazelentin.search_mode = search_mode;
+            let path_result = wazelentin.find_and_transmit_path().unwrap();
+            assert_eq!(path_result.exit_pos, (2, 0));
+            assert_eq!(path_result.path.len(), 3);
+            assert_eq!(path_result.path.last(), Some(&(2, 0)));
+        }
+    }
+
+    #[test]
+    fn test_nearest_exit_follows_the_cost() {
+        let mut grid_info = map_with_three_exits();
+        grid_info.grid[1][0] = '9';
+        grid_info.grid[1][1] = '9';
+        grid_info.grid[2][1] = '9';
+        let mut wazelentin = PathDetective::new(grid_info, None);
+        wazelentin.search_mode = SearchMode::Dijkstra;
+        let path_result = wazelentin.find_and_transmit_path().unwrap();
+        assert_eq!(path_result.exit_pos, (0, 6));
+        assert_eq!(path_result.cost, 4.0);
+    }
+
+    #[test]
+    fn test_unreachable_exits_are_skipped() {
+        let mut grid_info = map_with_three_exits();
+        for row in 0..3 {
+            grid_info.grid[row][1] = 'E';
+        }
+        let path_result = PathDetective::new(grid_info, None).find_and_transmit_path().unwrap();
+        assert_eq!(path_result.exit_pos, (0, 6));
+    }
+
+    #[test]
+    fn test_paths_from_every_start_go_to_their_nearest_exit() {
+        let mut grid_info = map_with_three_exits();
+        grid_info.start_positions = vec![(1, 2), (0, 5), (2, 5), (0, 0)];
+        let mut wazelentin = PathDetective::new(grid_info, None);
+        let path_results = wazelentin.find_and_transmit_paths_from_every_start();
+        assert_eq!(path_results.len(), 4);
+        assert_eq!(path_results[0].as_ref().unwrap().exit_pos, (2, 0));
+        assert_eq!(path_results[0].as_ref().unwrap().path.len(), 3);
+        assert_eq!(path_results[1].as_ref().unwrap().path, [(0, 5), (0, 6)]);
+        assert_eq!(path_results[2].as_ref().unwrap().exit_pos, (2, 6));
+        assert_eq!(path_results[3].as_ref().unwrap().length, 2.0);
+
+        wazelentin.search_mode = SearchMode::Dijkstra;
+        let single_path = wazelentin.find_and_transmit_path().unwrap();
+        assert_eq!(single_path.length, path_results[0].as_ref().unwrap().length);
+    }
+
+    #[test]
+    fn test_paths_from_every_start_flag_unreachable_starts() {
+        let mut grid_info = map_with_three_exits();
+        grid_info.exit_positions = BTreeSet::from([(2, 0)]);
+        grid_info.grid[2][6] = 'O';
+        grid_info.grid[0][6] = 'O';
+        for row in 0..3 {
+            grid_info.grid[row][3] = 'E';
+        }
+        grid_info.start_positions = vec![(1, 2), (0, 5)];
+        let (sender, receiver) = mpsc::channel::<(usize, usize)>();
+        let wazelentin = PathDetective::new(grid_info, Some(sender));
+        let path_results = wazelentin.find_and_transmit_paths_from_every_start();
+        drop(wazelentin);
+        assert!(path_results[0].is_some());
+        assert!(path_results[1].is_none());
+        let transmitted: Vec<(usize, usize)> = receiver.iter().collect();
+        assert_eq!(transmitted[transmitted.len() - 2..], [(1, 2), (usize::MAX, usize::MAX)]);
+    }
+
+    #[test]
+    fn test_paths_from_every_start_follow_the_octile_metric() {
+        let mut grid_info = map_with_two_ways();
+        grid_info.start_positions = vec![(2, 6), (3, 0)];
+        let mut wazelentin = PathDetective::new(grid_info, None);
+        wazelentin.metric = Metric::Octile;
+        let path_results = wazelentin.find_and_transmit_paths_from_every_start();
+        wazelentin.search_mode = SearchMode::Dijkstra;
+        let single_path = wazelentin.find_and_transmit_path().unwrap();
+        assert!((path_results[0].as_ref().unwrap().cost - single_path.cost).abs() < 1e-9);
+        assert_eq!(path_results[1].as_ref().unwrap().path, [(3, 0), (2, 0), (1, 0), (0, 0)]);
+    }
+
+    #[test]
+    fn test_paths_from_every_start_follow_the_search_mode() {
+        let mut grid_info = map_with_three_exits();
+        grid_info.start_positions = vec![(1, 2), (0, 5), (2, 5), (0, 0)];
+        let (sender, receiver) = mpsc::channel::<(usize, usize)>();
+        let mut wazelentin = PathDetective::new(grid_info, Some(sender));
+        wazelentin.search_mode = SearchMode::ThetaStar;
+        let path_results = wazelentin.find_and_transmit_paths();
+        let theta_results: Vec<_> = wazelentin
+            .grid_info
+            .start_positions
+            .iter()
+            .map(|&start_pos| ThetaStar.find_path(&wazelentin.search_grid(), start_pos, &wazelentin.grid_info.exit_positions))
+            .collect();
+        assert_eq!(theta_results, path_results);
+        assert!(wazelentin.expanded_cases() > 0);
+        drop(wazelentin);
+        // the paths are sent one after the other, then the end once
+        let transmitted: Vec<(usize, usize)> = receiver.iter().collect();
+        assert_eq!(1, transmitted.iter().filter(|&&pos| pos == (usize::MAX, usize::MAX)).count());
+        assert_eq!(Some(&(usize::MAX, usize::MAX)), transmitted.last());
+    }
+
+    #[test]
+    fn test_path_with_millions_of_steps() {
+        let column_max = 2_000_000;
+        let mut corridor = vec![GridInfo::CASE_OPEN; column_max + 1];
+        corridor[0] = GridInfo::CASE_WIN;
+        let grid_info = GridInfo {
+            start_positions: vec![(0, column_max)],
+            exit_positions: BTreeSet::from([(0, 0)]),
+            metadata: vec![],
+            legend: Legend::default(),
+            row_max: 0,
+            column_max,
+            grid: [corridor].to_vec(),
+        };
+        for search_mode in [SearchMode::Bfs, SearchMode::Dijkstra] {
+            let mut wazelentin = PathDetective::new(grid_info.clone(), None);
+            wazelentin.search_mode = search_mode;
+            let path = wazelentin.find_and_transmit_path().unwrap().path;
+            assert_eq!(path.len(), column_max + 1);
+            assert_eq!(path[1], (0, column_max - 1));
+            assert_eq!(path[column_max], (0, 0));
+        }
+    }
+
+    #[test]
+    fn test_astar_expands_fewer_cases_than_dijkstra() {
+        let mut wazelentin = PathDetective::new(map_with_two_ways(), None);
+        wazelentin.search_mode = SearchMode::Dijkstra;
+        wazelentin.find_and_transmit_path().unwrap();
+        let dijkstra_expanded_cases = wazelentin.expanded_cases();
+        wazelentin.search_mode = SearchMode::AStar(Heuristic::Chebyshev);
+        wazelentin.find_and_transmit_path().unwrap();
+        assert!(wazelentin.expanded_cases() > 0);
+        assert!(wazelentin.expanded_cases() < dijkstra_expanded_cases);
     }
 }
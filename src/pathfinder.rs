@@ -0,0 +1,346 @@
+use crate::path_detective::{Connectivity, Heuristic, Metric, PathResult};
+use crate::GridInfo;
+use std::cell::Cell;
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, BTreeSet, VecDeque};
+
+/// Graph of the open cases of a grid, with the moves allowed between them and their cost
+pub struct SearchGrid<'a> {
+    pub grid_info: &'a GridInfo,
+    pub metric: Metric,
+    pub connectivity: Connectivity,
+    expanded_cases: Cell<usize>,
+}
+
+impl<'a> SearchGrid<'a> {
+    const DIRECTIONS: [(isize, isize); 8] = [
+        (-1, 0),  /* DOWN */
+        (-1, 1),  /* DOWN RIGHT */
+        (0, 1),   /* RIGHT */
+        (1, 1),   /* UP RIGHT */
+        (1, 0),   /* UP */
+        (1, -1),  /* UP LEFT */
+        (0, -1),  /* LEFT */
+        (-1, -1), /* DOWN LEFT */
+    ];
+
+    pub fn new(grid_info: &'a GridInfo, metric: Metric, connectivity: Connectivity) -> SearchGrid<'a> {
+        SearchGrid {
+            grid_info,
+            metric,
+            connectivity,
+            expanded_cases: Cell::new(0),
+        }
+    }
+
+    /// Number of cases whose neighbours were explored since the grid was built
+    pub fn expanded_cases(&self) -> usize {
+        self.expanded_cases.get()
+    }
+
+    pub fn count_expanded_case(&self) {
+        self.expanded_cases.set(self.expanded_cases.get() + 1);
+    }
+
+    fn width(&self) -> usize {
+        self.grid_info.grid.first().map_or(0, Vec::len)
+    }
+
+    /// Index of a case in the flat arrays of the searches: row * width + column
+    pub fn index(&self, pos: (usize, usize)) -> usize {
+        pos.0 * self.width() + pos.1
+    }
+
+    pub fn pos(&self, index: usize) -> (usize, usize) {
+        (index / self.width(), index % self.width())
+    }
+
+    pub fn case_count(&self) -> usize {
+        self.grid_info.grid.len() * self.width()
+    }
+
+    pub fn is_valid_case(&self, row: usize, column: usize) -> bool {
+        if let Some(case) = self.grid_info.grid.get(row).and_then(|r| r.get(column)) {
+            *case != GridInfo::CASE_CLOSE
+        } else {
+            false
+        }
+    }
+
+    /// Cases reachable in one move, in the order of `DIRECTIONS`
+    pub fn connected_cases(&self, row: usize, column: usize) -> impl Iterator<Item = (usize, usize)> + '_ {
+        SearchGrid::DIRECTIONS
+            .iter()
+            .filter_map(move |&(row_offset, column_offset)| {
+                if row_offset < 0 && row == 0 {
+                    return None;
+                }
+                if column_offset < 0 && column == 0 {
+                    return None;
+                }
+                let new_row = (row as isize + row_offset) as usize;
+                let new_column = (column as isize + column_offset) as usize;
+                if row_offset != 0 && column_offset != 0 {
+                    match self.connectivity {
+                        Connectivity::VonNeumann => return None,
+                        Connectivity::Moore => {}
+                        Connectivity::MooreNoCornerCutting => {
+                            if !self.is_valid_case(new_row, column) || !self.is_valid_case(row, new_column) {
+                                return None;
+                            }
+                        }
+                    }
+                }
+                if self.is_valid_case(new_row, new_column) {
+                    Some((new_row, new_column))
+                } else {
+                    None
+                }
+            })
+    }
+
+    fn direction_index(from: (usize, usize), to: (usize, usize)) -> usize {
+        let offset = (to.0 as isize - from.0 as isize, to.1 as isize - from.1 as isize);
+        SearchGrid::DIRECTIONS
+            .iter()
+            .position(|&direction| direction == offset)
+            .unwrap_or(usize::MAX)
+    }
+
+    /// Follow the parents from the last case of the path back to the start (which has no parent)
+    pub fn rebuild_path(&self, parents: &[usize], last_pos: (usize, usize)) -> Vec<(usize, usize)> {
+        let mut shortest_path = vec![last_pos];
+        let mut current_index = self.index(last_pos);
+        while parents[current_index] != usize::MAX {
+            current_index = parents[current_index];
+            shortest_path.push(self.pos(current_index));
+        }
+        shortest_path.reverse();
+        shortest_path
+    }
+
+    /// Length of the move with the metric multiplied by the cost of the case stepped on
+    pub fn move_cost(&self, from: (usize, usize), to: (usize, usize)) -> f64 {
+        let case_cost = GridInfo::case_cost(self.grid_info.grid[to.0][to.1]).map_or(f64::INFINITY, |cost| cost as f64);
+        case_cost * self.metric.move_length(from, to)
+    }
+
+    // a float sum starts by -0.0, fold from 0.0 so that a path without move has a length of 0
+    fn path_length(&self, path: &[(usize, usize)]) -> f64 {
+        path.windows(2).fold(0.0, |length, step| length + self.metric.move_length(step[0], step[1]))
+    }
+
+    fn path_cost(&self, path: &[(usize, usize)]) -> f64 {
+        path.windows(2).fold(0.0, |cost, step| cost + self.move_cost(step[0], step[1]))
+    }
+
+    /// Result of a path from a start to an exit, measured with the metric of the grid
+    pub fn path_result(&self, path: Vec<(usize, usize)>) -> PathResult {
+        PathResult {
+            exit_pos: *path.last().unwrap(),
+            length: self.path_length(&path),
+            cost: self.path_cost(&path),
+            path,
+        }
+    }
+}
+
+/// Algorithm finding a path from a start to the nearest of the exits
+pub trait Pathfinder {
+    /// name of the algorithm, as given to `SearchMode::from_name`
+    fn name(&self) -> &'static str;
+
+    /// Whether the path is the cheapest one with the metric and the weighted cases,
+    /// false when they are ignored
+    fn follows_costs(&self) -> bool {
+        true
+    }
+
+    /// Whether the path is a list of waypoints in line of sight of each other,
+    /// false when it goes from a case to a connected one
+    fn is_any_angle(&self) -> bool {
+        false
+    }
+
+    /// Path from `start_pos` to the nearest exit (both included), None when no exit can be reached
+    fn find_path(&self, search_grid: &SearchGrid, start_pos: (usize, usize), exit_positions: &BTreeSet<(usize, usize)>) -> Option<PathResult>;
+}
+
+/// Breadth first search: every move counts as 1, the weight of the cases and the metric are ignored
+pub struct Bfs;
+
+/// Cheapest path on weighted cases, by increasing cost from the start
+pub struct Dijkstra;
+
+/// Dijkstra guided toward the closest exit by an heuristic, stops as soon as an exit is reached
+pub struct AStar(pub Heuristic);
+
+impl Pathfinder for Bfs {
+    fn name(&self) -> &'static str {
+        "bfs"
+    }
+
+    fn follows_costs(&self) -> bool {
+        false
+    }
+
+    fn find_path(&self, search_grid: &SearchGrid, start_pos: (usize, usize), exit_positions: &BTreeSet<(usize, usize)>) -> Option<PathResult> {
+        if !search_grid.is_valid_case(start_pos.0, start_pos.1) {
+            return None;
+        }
+        // the exits are only looked for among the neighbours of the cases reached
+        if exit_positions.contains(&start_pos) {
+            return Some(search_grid.path_result(vec![start_pos]));
+        }
+        let mut parents: Vec<usize> = vec![usize::MAX; search_grid.case_count()];
+        let mut queue: VecDeque<(usize, usize)> = VecDeque::new();
+        // a case gets its distance when queued, so it is never queued twice
+        let mut distances: Vec<usize> = vec![usize::MAX; search_grid.case_count()];
+        distances[search_grid.index(start_pos)] = 0;
+        queue.push_back(start_pos);
+
+        while let Some(current_pos) = queue.pop_front() {
+            search_grid.count_expanded_case();
+            let current_index = search_grid.index(current_pos);
+            let distance = distances[current_index];
+            // the nearest exit is found once a case next to it is reached
+            if let Some(exit_pos) = search_grid
+                .connected_cases(current_pos.0, current_pos.1)
+                .find(|connected_pos| exit_positions.contains(connected_pos))
+            {
+                parents[search_grid.index(exit_pos)] = current_index;
+                return Some(search_grid.path_result(search_grid.rebuild_path(&parents, exit_pos)));
+            }
+
+            for connected_pos in search_grid.connected_cases(current_pos.0, current_pos.1) {
+                let connected_index = search_grid.index(connected_pos);
+                if distances[connected_index] == usize::MAX {
+                    distances[connected_index] = distance + 1;
+                    parents[connected_index] = current_index;
+                    queue.push_back(connected_pos);
+                } else if distances[connected_index] == distance + 1
+                    && SearchGrid::direction_index(connected_pos, current_pos)
+                        < SearchGrid::direction_index(connected_pos, search_grid.pos(parents[connected_index]))
+                {
+                    // among the parents at the same distance keep the first one in the order of DIRECTIONS
+                    parents[connected_index] = current_index;
+                }
+            }
+        }
+        None
+    }
+}
+
+impl Pathfinder for Dijkstra {
+    fn name(&self) -> &'static str {
+        "dijkstra"
+    }
+
+    fn find_path(&self, search_grid: &SearchGrid, start_pos: (usize, usize), exit_positions: &BTreeSet<(usize, usize)>) -> Option<PathResult> {
+        find_path_with_best_first(search_grid, start_pos, exit_positions, None)
+    }
+}
+
+impl Pathfinder for AStar {
+    fn name(&self) -> &'static str {
+        "astar"
+    }
+
+    fn find_path(&self, search_grid: &SearchGrid, start_pos: (usize, usize), exit_positions: &BTreeSet<(usize, usize)>) -> Option<PathResult> {
+        find_path_with_best_first(search_grid, start_pos, exit_positions, Some(self.0))
+    }
+}
+
+/// Case waiting in the A* / Dijkstra open list, the lowest estimated cost comes out first
+#[derive(PartialEq)]
+pub(crate) struct OpenCase {
+    pub(crate) estimated_cost: f64,
+    pub(crate) cost_from_start: f64,
+    pub(crate) pos: (usize, usize),
+}
+
+impl Eq for OpenCase {}
+
+impl Ord for OpenCase {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // BinaryHeap is a max heap: reverse the estimation, and on a tie prefer the case closest to the exit
+        other
+            .estimated_cost
+            .total_cmp(&self.estimated_cost)
+            .then(self.cost_from_start.total_cmp(&other.cost_from_start))
+    }
+}
+
+impl PartialOrd for OpenCase {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Dijkstra without heuristic, A* with one (estimated toward the closest exit, so it stays admissible)
+fn find_path_with_best_first(
+    search_grid: &SearchGrid,
+    start_pos: (usize, usize),
+    exit_positions: &BTreeSet<(usize, usize)>,
+    heuristic: Option<Heuristic>,
+) -> Option<PathResult> {
+    if !search_grid.is_valid_case(start_pos.0, start_pos.1) {
+        return None;
+    }
+    let estimate = |pos| {
+        heuristic.map_or(0.0, |heuristic| {
+            exit_positions
+                .iter()
+                .map(|&exit_pos| heuristic.distance(pos, exit_pos))
+                .fold(f64::INFINITY, f64::min)
+        })
+    };
+    let mut costs: Vec<f64> = vec![f64::INFINITY; search_grid.case_count()];
+    let mut parents: Vec<usize> = vec![usize::MAX; search_grid.case_count()];
+    let mut open_cases: BinaryHeap<OpenCase> = BinaryHeap::new();
+
+    costs[search_grid.index(start_pos)] = 0.0;
+    open_cases.push(OpenCase {
+        estimated_cost: estimate(start_pos),
+        cost_from_start: 0.0,
+        pos: start_pos,
+    });
+    while let Some(OpenCase { cost_from_start, pos, .. }) = open_cases.pop() {
+        if exit_positions.contains(&pos) {
+            return Some(search_grid.path_result(search_grid.rebuild_path(&parents, pos)));
+        }
+        if cost_from_start > costs[search_grid.index(pos)] {
+            // already reached with a lower cost
+            continue;
+        }
+        search_grid.count_expanded_case();
+        for connected_pos in search_grid.connected_cases(pos.0, pos.1) {
+            let cost = cost_from_start + search_grid.move_cost(pos, connected_pos);
+            let connected_index = search_grid.index(connected_pos);
+            if cost < costs[connected_index] {
+                costs[connected_index] = cost;
+                parents[connected_index] = search_grid.index(pos);
+                open_cases.push(OpenCase {
+                    estimated_cost: cost + estimate(connected_pos),
+                    cost_from_start: cost,
+                    pos: connected_pos,
+                });
+            }
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    /// Search from the first start of `rows`, lines of the map format (top row first), with the Moore moves.
+    /// Returns the path found and the number of cases expanded by the search
+    pub(crate) fn solve(rows: &str, pathfinder: &dyn Pathfinder, metric: Metric) -> (Option<PathResult>, usize) {
+        let grid_info = GridInfo::new(rows).unwrap();
+        let search_grid = SearchGrid::new(&grid_info, metric, Connectivity::Moore);
+        let path_result = pathfinder.find_path(&search_grid, grid_info.start_positions[0], &grid_info.exit_positions);
+        (path_result, search_grid.expanded_cases())
+    }
+}
@@ -0,0 +1,152 @@
+use crate::path_detective::{PathResult, SearchMode};
+use clap::ValueEnum;
+use serde::Serialize;
+use std::time::Duration;
+
+/// Result of a run, serialized for `--format json`
+#[derive(Serialize, Debug)]
+pub struct SolveReport {
+    pub grid_file: String,
+    /// name of the search mode, as given to `--search`
+    pub search_mode: String,
+    /// name of the heuristic for the A* search, as given to `--heuristic`
+    pub heuristic: Option<String>,
+    /// cases whose neighbours were explored, for every start together
+    pub expanded_cases: usize,
+    pub elapsed_ms: f64,
+    /// one per start, in the order of the map header
+    pub paths: Vec<PathReport>,
+}
+
+/// Result of a map which cannot be read, serialized for `--format json` in place of its `SolveReport`
+#[derive(Serialize, Debug)]
+pub struct ErrorReport {
+    pub grid_file: String,
+    pub error: String,
+}
+
+#[derive(Serialize, Debug)]
+pub struct PathReport {
+    pub start: (usize, usize),
+    /// None when no exit can be reached from the start
+    pub exit: Option<(usize, usize)>,
+    pub path: Option<Vec<(usize, usize)>>,
+    /// number of moves of the path
+    pub steps: Option<usize>,
+    pub length: Option<f64>,
+    pub cost: Option<f64>,
+}
+
+impl PathReport {
+    pub fn new(start: (usize, usize), path_result: Option<PathResult>) -> PathReport {
+        match path_result {
+            Some(path_result) => PathReport {
+                start,
+                exit: Some(path_result.exit_pos),
+                steps: Some(path_result.path.len() - 1),
+                length: Some(path_result.length),
+                cost: Some(path_result.cost),
+                path: Some(path_result.path),
+            },
+            None => PathReport {
+                start,
+                exit: None,
+                path: None,
+                steps: None,
+                length: None,
+                cost: None,
+            },
+        }
+    }
+}
+
+impl SolveReport {
+    pub fn new(grid_file: &str, search_mode: SearchMode, expanded_cases: usize, elapsed: Duration, paths: Vec<PathReport>) -> SolveReport {
+        let heuristic = match search_mode {
+            SearchMode::AStar(heuristic) => heuristic.to_possible_value().map(|value| value.get_name().to_string()),
+            _ => None,
+        };
+        SolveReport {
+            grid_file: grid_file.to_string(),
+            search_mode: search_mode.pathfinder().name().to_string(),
+            heuristic,
+            expanded_cases,
+            elapsed_ms: elapsed.as_secs_f64() * 1000.0,
+            paths,
+        }
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("a solve report is always serializable")
+    }
+}
+
+impl ErrorReport {
+    pub fn new(grid_file: &str, error: &impl std::fmt::Display) -> ErrorReport {
+        ErrorReport {
+            grid_file: grid_file.to_string(),
+            error: error.to_string(),
+        }
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("an error report is always serializable")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::path_detective::Heuristic;
+    #[test]
+    fn test_json_of_a_found_path() {
+        let path_result = PathResult {
+            path: vec![(0, 2), (1, 1), (2, 1)],
+            exit_pos: (2, 1),
+            length: 2.0,
+            cost: 2.0,
+        };
+        let solve_report = SolveReport::new(
+            "data/map",
+            SearchMode::Bfs,
+            5,
+            Duration::from_millis(3),
+            vec![PathReport::new((0, 2), Some(path_result))],
+        );
+        let json: serde_json::Value = serde_json::from_str(&solve_report.to_json()).unwrap();
+        assert_eq!(json["grid_file"], "data/map");
+        assert_eq!(json["search_mode"], "bfs");
+        assert!(json["heuristic"].is_null());
+        assert_eq!(json["expanded_cases"], 5);
+        assert_eq!(json["elapsed_ms"], 3.0);
+        assert_eq!(json["paths"][0]["start"], serde_json::json!([0, 2]));
+        assert_eq!(json["paths"][0]["exit"], serde_json::json!([2, 1]));
+        assert_eq!(json["paths"][0]["path"], serde_json::json!([[0, 2], [1, 1], [2, 1]]));
+        assert_eq!(json["paths"][0]["steps"], 2);
+        assert_eq!(json["paths"][0]["length"], 2.0);
+        assert_eq!(json["paths"][0]["cost"], 2.0);
+    }
+    #[test]
+    fn test_json_of_an_unreachable_exit() {
+        let solve_report = SolveReport::new("data/map", SearchMode::Bfs, 1, Duration::ZERO, vec![PathReport::new((0, 2), None)]);
+        let json: serde_json::Value = serde_json::from_str(&solve_report.to_json()).unwrap();
+        assert_eq!(json["paths"][0]["start"], serde_json::json!([0, 2]));
+        assert!(json["paths"][0]["exit"].is_null());
+        assert!(json["paths"][0]["path"].is_null());
+        assert!(json["paths"][0]["cost"].is_null());
+    }
+    #[test]
+    fn test_json_search_mode_should_be_the_names_of_the_command_line() {
+        let solve_report = SolveReport::new("data/map", SearchMode::AStar(Heuristic::Octile), 1, Duration::ZERO, vec![]);
+        let json: serde_json::Value = serde_json::from_str(&solve_report.to_json()).unwrap();
+        assert_eq!(json["search_mode"], "astar");
+        assert_eq!(json["heuristic"], "octile");
+    }
+    #[test]
+    fn test_json_of_a_wrong_map() {
+        let error_report = ErrorReport::new("<stdin> (map 2)", &"line 3, column 1: wrong map");
+        let json: serde_json::Value = serde_json::from_str(&error_report.to_json()).unwrap();
+        assert_eq!(json["grid_file"], "<stdin> (map 2)");
+        assert_eq!(json["error"], "line 3, column 1: wrong map");
+    }
+}
@@ -0,0 +1,195 @@
+use crate::path_detective::PathResult;
+use crate::pathfinder::{OpenCase, Pathfinder, SearchGrid};
+use std::collections::{BTreeSet, BinaryHeap};
+
+/// Theta*: an A* whose cases take as parent the parent of their neighbour when it is in line of sight,
+/// so the path is a list of waypoints joined by straight lines at any angle instead of grid moves.
+/// The length is the Euclidean one whatever the metric, and the weighted cases are crossed as open ones,
+/// so the cost of the path is its length.
+/// Every segment is in line of sight, so a diagonal move never cuts a corner whatever the connectivity
+pub struct ThetaStar;
+
+/// Straight line length between the centers of two cases
+fn euclidean_distance(from: (usize, usize), to: (usize, usize)) -> f64 {
+    (from.0.abs_diff(to.0) as f64).hypot(from.1.abs_diff(to.1) as f64)
+}
+
+/// Every case crossed by the line between the centers of two cases, from `from` to `to`.
+/// When the line goes through a corner both cases of the corner are crossed,
+/// so a line of sight never cuts a blocked corner nor squeezes between two blocked cases
+pub fn crossed_cases(from: (usize, usize), to: (usize, usize)) -> Vec<(usize, usize)> {
+    let row_delta = from.0.abs_diff(to.0) as isize;
+    let column_delta = from.1.abs_diff(to.1) as isize;
+    let row_step = if to.0 < from.0 { -1 } else { 1 };
+    let column_step = if to.1 < from.1 { -1 } else { 1 };
+    let (mut row, mut column) = (from.0 as isize, from.1 as isize);
+    let (mut row_crossings, mut column_crossings) = (0, 0);
+    let mut cases = vec![from];
+    while row_crossings < row_delta || column_crossings < column_delta {
+        // the line crosses the next row border at (0.5 + row_crossings) / row_delta of its length,
+        // and the next column border at (0.5 + column_crossings) / column_delta
+        let decision = (1 + 2 * row_crossings) * column_delta - (1 + 2 * column_crossings) * row_delta;
+        if decision == 0 {
+            cases.push(((row + row_step) as usize, column as usize));
+            cases.push((row as usize, (column + column_step) as usize));
+        }
+        if decision <= 0 {
+            row += row_step;
+            row_crossings += 1;
+        }
+        if decision >= 0 {
+            column += column_step;
+            column_crossings += 1;
+        }
+        cases.push((row as usize, column as usize));
+    }
+    cases
+}
+
+/// Every case crossed by the segments between the waypoints of a path, each one once
+pub fn cases_of_waypoints(waypoints: &[(usize, usize)]) -> Vec<(usize, usize)> {
+    let mut cases = waypoints.first().map_or(vec![], |&start_pos| vec![start_pos]);
+    for segment in waypoints.windows(2) {
+        cases.extend(crossed_cases(segment[0], segment[1]).into_iter().skip(1));
+    }
+    cases
+}
+
+/// Whether no blocked case stands between the centers of two cases
+pub fn line_of_sight(search_grid: &SearchGrid, from: (usize, usize), to: (usize, usize)) -> bool {
+    crossed_cases(from, to).into_iter().all(|(row, column)| search_grid.is_valid_case(row, column))
+}
+
+impl ThetaStar {
+    fn path_result(waypoints: Vec<(usize, usize)>) -> PathResult {
+        let length = waypoints.windows(2).fold(0.0, |length, segment| length + euclidean_distance(segment[0], segment[1]));
+        PathResult {
+            exit_pos: *waypoints.last().unwrap(),
+            path: waypoints,
+            length,
+            cost: length,
+        }
+    }
+}
+
+impl Pathfinder for ThetaStar {
+    fn name(&self) -> &'static str {
+        "theta"
+    }
+
+    fn follows_costs(&self) -> bool {
+        false
+    }
+
+    fn is_any_angle(&self) -> bool {
+        true
+    }
+
+    fn find_path(&self, search_grid: &SearchGrid, start_pos: (usize, usize), exit_positions: &BTreeSet<(usize, usize)>) -> Option<PathResult> {
+        if !search_grid.is_valid_case(start_pos.0, start_pos.1) {
+            return None;
+        }
+        let estimate = |pos| {
+            exit_positions
+                .iter()
+                .map(|&exit_pos| euclidean_distance(pos, exit_pos))
+                .fold(f64::INFINITY, f64::min)
+        };
+        let mut costs: Vec<f64> = vec![f64::INFINITY; search_grid.case_count()];
+        // previous waypoint of every case reached
+        let mut parents: Vec<usize> = vec![usize::MAX; search_grid.case_count()];
+        let mut open_cases: BinaryHeap<OpenCase> = BinaryHeap::new();
+
+        costs[search_grid.index(start_pos)] = 0.0;
+        open_cases.push(OpenCase {
+            estimated_cost: estimate(start_pos),
+            cost_from_start: 0.0,
+            pos: start_pos,
+        });
+        while let Some(OpenCase { cost_from_start, pos, .. }) = open_cases.pop() {
+            if exit_positions.contains(&pos) {
+                return Some(ThetaStar::path_result(search_grid.rebuild_path(&parents, pos)));
+            }
+            if cost_from_start > costs[search_grid.index(pos)] {
+                continue;
+            }
+            search_grid.count_expanded_case();
+            let parent_index = parents[search_grid.index(pos)];
+            for connected_pos in search_grid.connected_cases(pos.0, pos.1) {
+                if !line_of_sight(search_grid, pos, connected_pos) {
+                    continue;
+                }
+                // skip the case when the parent sees the neighbour, as a string pulled tight
+                let (waypoint_index, cost) = match parent_index {
+                    usize::MAX => (search_grid.index(pos), cost_from_start + euclidean_distance(pos, connected_pos)),
+                    parent_index if line_of_sight(search_grid, search_grid.pos(parent_index), connected_pos) => {
+                        let parent_pos = search_grid.pos(parent_index);
+                        (parent_index, costs[parent_index] + euclidean_distance(parent_pos, connected_pos))
+                    }
+                    _ => (search_grid.index(pos), cost_from_start + euclidean_distance(pos, connected_pos)),
+                };
+                let connected_index = search_grid.index(connected_pos);
+                if cost < costs[connected_index] {
+                    costs[connected_index] = cost;
+                    parents[connected_index] = waypoint_index;
+                    open_cases.push(OpenCase {
+                        estimated_cost: cost + estimate(connected_pos),
+                        cost_from_start: cost,
+                        pos: connected_pos,
+                    });
+                }
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::path_detective::{Connectivity, Metric};
+    use crate::pathfinder::tests::solve;
+    use crate::GridInfo;
+
+    #[test]
+    fn test_crossed_cases() {
+        assert_eq!(vec![(0, 0), (0, 1), (1, 1), (0, 2), (1, 2), (1, 3)], crossed_cases((0, 0), (1, 3)));
+        // the row border is crossed in the middle of the column 2
+        assert_eq!(vec![(0, 0), (0, 1), (0, 2), (1, 2), (1, 3), (1, 4)], crossed_cases((0, 0), (1, 4)));
+        assert_eq!(vec![(2, 1), (1, 1), (0, 1)], crossed_cases((2, 1), (0, 1)));
+        // through the corner between (0, 0) and (1, 1)
+        assert_eq!(vec![(0, 0), (1, 0), (0, 1), (1, 1)], crossed_cases((0, 0), (1, 1)));
+        assert_eq!(vec![(3, 3)], crossed_cases((3, 3), (3, 3)));
+    }
+
+    #[test]
+    fn test_theta_goes_straight_across_an_open_map() {
+        let path_result = solve("OOOOOOX\nOOOOOOO\nSOOOOOO", &ThetaStar, Metric::Octile).0.unwrap();
+        assert_eq!(vec![(0, 0), (2, 6)], path_result.path);
+        assert!((path_result.length - 40.0_f64.sqrt()).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_theta_turns_at_the_corner_of_a_wall() {
+        let path_result = solve("OOOOOOOX\nOOOEEEEE\nSOOOOOOO", &ThetaStar, Metric::Octile).0.unwrap();
+        // (1, 2) does not see the exit, the line between them goes through the corner of the wall
+        assert_eq!(vec![(0, 0), (2, 3), (2, 7)], path_result.path);
+        assert!((path_result.length - (13.0_f64.sqrt() + 4.0)).abs() < 1e-9);
+        let grid_info = GridInfo::new("OOOOOOOX\nOOOEEEEE\nSOOOOOOO").unwrap();
+        let search_grid = SearchGrid::new(&grid_info, Metric::Octile, Connectivity::Moore);
+        assert!(path_result.path.windows(2).all(|segment| line_of_sight(&search_grid, segment[0], segment[1])));
+        assert!(!line_of_sight(&search_grid, (0, 0), (2, 7)));
+    }
+
+    #[test]
+    fn test_theta_crosses_the_weighted_cases_as_open_ones() {
+        let path_result = solve("OOOOX\nO999O\nSOOOO", &ThetaStar, Metric::Octile).0.unwrap();
+        assert_eq!(vec![(0, 0), (2, 4)], path_result.path);
+        assert_eq!(path_result.length, path_result.cost);
+    }
+
+    #[test]
+    fn test_theta_should_return_none_without_reachable_exit() {
+        assert!(solve("SOEOX\nOOEOO", &ThetaStar, Metric::Octile).0.is_none());
+    }
+}
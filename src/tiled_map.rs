@@ -0,0 +1,349 @@
+use crate::grid_info::grid;
+use crate::GridInfo;
+use serde::Deserialize;
+use std::fmt;
+
+/// Problem found while reading a Tiled map or turning it into a grid
+#[derive(PartialEq, Debug)]
+pub enum Error {
+    /// not a Tiled JSON (.tmj / .json) or TMX (.tmx) map, or a part of it is missing
+    WrongMap(String),
+    /// no tile layer with the name of the collision layer
+    MissingLayer(String),
+    /// infinite map, or layer data neither in CSV nor in XML (base64 and compressed data are not read)
+    UnsupportedLayer(String),
+    /// start or exit object outside of the map or on a tile of the collision layer, x from the left and y from the top
+    MarkerOnBlockedCase { name: String, x: usize, y: usize },
+    /// the map is not a valid grid, the line of the error is y + 1 and its column x + 1
+    Grid(grid::Error),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::WrongMap(reason) => write!(f, "wrong Tiled map: {}", reason),
+            Error::MissingLayer(layer_name) => write!(f, "no tile layer named \"{}\"", layer_name),
+            Error::UnsupportedLayer(reason) => write!(f, "unsupported layer: {}", reason),
+            Error::MarkerOnBlockedCase { name, x, y } => {
+                write!(f, "the {} object on the tile ({}, {}) is out of the map or on the collision layer", name, x, y)
+            }
+            Error::Grid(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// Object of an object layer, in pixels
+#[derive(Deserialize, Debug, Default)]
+struct TiledObject {
+    #[serde(default)]
+    name: String,
+    /// `type` up to Tiled 1.8, `class` since
+    #[serde(default, rename = "type")]
+    object_type: String,
+    #[serde(default)]
+    class: String,
+    x: f64,
+    y: f64,
+    #[serde(default)]
+    width: f64,
+    #[serde(default)]
+    height: f64,
+    /// tile objects are placed by their bottom left corner
+    gid: Option<u32>,
+}
+
+#[derive(Deserialize, Debug)]
+struct TiledLayer {
+    #[serde(default)]
+    name: String,
+    #[serde(rename = "type")]
+    layer_type: String,
+    /// array of global tile ids, or a base64 string
+    data: Option<serde_json::Value>,
+    #[serde(default)]
+    objects: Vec<TiledObject>,
+    /// layers of a group layer
+    #[serde(default)]
+    layers: Vec<TiledLayer>,
+}
+
+#[derive(Deserialize, Debug)]
+struct TiledJson {
+    width: usize,
+    height: usize,
+    tilewidth: f64,
+    tileheight: f64,
+    #[serde(default)]
+    infinite: bool,
+    layers: Vec<TiledLayer>,
+}
+
+/// Tiles of a Tiled map seen from above: the collision layer and the start / exit objects
+#[derive(Clone, Debug, PartialEq)]
+pub struct TiledMap {
+    pub width: usize,
+    pub height: usize,
+    /// a tile of the collision layer blocks its case, row by row from the top left corner
+    pub walls: Vec<bool>,
+    /// (x, y) of the tiles holding a start object
+    pub starts: Vec<(usize, usize)>,
+    /// (x, y) of the tiles holding an exit object
+    pub exits: Vec<(usize, usize)>,
+}
+
+impl TiledMap {
+    /// name of the tile layer read when none is given
+    pub const COLLISION_LAYER: &'static str = "collision";
+    pub const START: &'static str = "start";
+    pub const EXIT: &'static str = "exit";
+
+    /// Whether a file is read as a Tiled map, from its extension
+    pub fn is_tiled_file(file_name: &str) -> bool {
+        let file_name = file_name.to_ascii_lowercase();
+        [".tmx", ".tmj", ".json"].iter().any(|extension| file_name.ends_with(extension))
+    }
+
+    /// Read a TMX map when the file starts like XML, a JSON map otherwise
+    pub fn new(map_file: &str, layer_name: &str) -> Result<TiledMap, Error> {
+        if map_file.trim_start().starts_with('<') {
+            TiledMap::from_tmx(map_file, layer_name)
+        } else {
+            TiledMap::from_json(map_file, layer_name)
+        }
+    }
+
+    pub fn from_json(map_file: &str, layer_name: &str) -> Result<TiledMap, Error> {
+        let tiled_json: TiledJson = serde_json::from_str(map_file).map_err(|error| Error::WrongMap(error.to_string()))?;
+        if tiled_json.infinite {
+            return Err(Error::UnsupportedLayer("infinite maps are not read".to_string()));
+        }
+        // group layers are flattened
+        let mut layers: Vec<&TiledLayer> = tiled_json.layers.iter().collect();
+        let mut layer_index = 0;
+        while layer_index < layers.len() {
+            layers.extend(layers[layer_index].layers.iter());
+            layer_index += 1;
+        }
+        let collision_layer = layers
+            .iter()
+            .find(|layer| layer.layer_type == "tilelayer" && layer.name == layer_name)
+            .ok_or(Error::MissingLayer(layer_name.to_string()))?;
+        let data: Vec<u32> = match &collision_layer.data {
+            Some(serde_json::Value::Array(tiles)) => tiles
+                .iter()
+                .map(|tile| tile.as_u64().map(|tile| tile as u32))
+                .collect::<Option<_>>()
+                .ok_or(Error::WrongMap("the tiles must be integers".to_string()))?,
+            _ => return Err(Error::UnsupportedLayer("save the layer data as CSV".to_string())),
+        };
+        let objects: Vec<&TiledObject> = layers.iter().flat_map(|layer| layer.objects.iter()).collect();
+        TiledMap::from_layers(tiled_json.width, tiled_json.height, (tiled_json.tilewidth, tiled_json.tileheight), &data, &objects)
+    }
+
+    pub fn from_tmx(map_file: &str, layer_name: &str) -> Result<TiledMap, Error> {
+        let document = roxmltree::Document::parse(map_file).map_err(|error| Error::WrongMap(error.to_string()))?;
+        let map = document.root_element();
+        if !map.has_tag_name("map") {
+            return Err(Error::WrongMap("the root element must be <map>".to_string()));
+        }
+        fn attribute<T: std::str::FromStr>(node: roxmltree::Node, name: &str) -> Result<T, Error> {
+            node.attribute(name)
+                .and_then(|value| value.parse::<T>().ok())
+                .ok_or(Error::WrongMap(format!("missing or wrong {} of <{}>", name, node.tag_name().name())))
+        }
+        if map.attribute("infinite") == Some("1") {
+            return Err(Error::UnsupportedLayer("infinite maps are not read".to_string()));
+        }
+        let collision_layer = map
+            .descendants()
+            .find(|node| node.has_tag_name("layer") && node.attribute("name") == Some(layer_name))
+            .ok_or(Error::MissingLayer(layer_name.to_string()))?;
+        let layer_data = collision_layer
+            .children()
+            .find(|node| node.has_tag_name("data"))
+            .ok_or(Error::WrongMap("missing <data> of the layer".to_string()))?;
+        let data: Vec<u32> = match layer_data.attribute("encoding") {
+            Some("csv") => layer_data
+                .text()
+                .unwrap_or("")
+                .split(',')
+                .map(|tile| tile.trim().parse::<u32>().map_err(|_| Error::WrongMap(format!("wrong tile \"{}\"", tile.trim()))))
+                .collect::<Result<_, _>>()?,
+            None => layer_data
+                .children()
+                .filter(|node| node.has_tag_name("tile"))
+                .map(|tile| attribute(tile, "gid").or(Ok(0)))
+                .collect::<Result<_, _>>()?,
+            Some(encoding) => return Err(Error::UnsupportedLayer(format!("{} data, save the layer data as CSV", encoding))),
+        };
+        let objects = map
+            .descendants()
+            .filter(|node| node.has_tag_name("object"))
+            .map(|object| {
+                Ok(TiledObject {
+                    name: object.attribute("name").unwrap_or("").to_string(),
+                    object_type: object.attribute("type").unwrap_or("").to_string(),
+                    class: object.attribute("class").unwrap_or("").to_string(),
+                    x: attribute(object, "x")?,
+                    y: attribute(object, "y")?,
+                    width: attribute(object, "width").unwrap_or(0.0),
+                    height: attribute(object, "height").unwrap_or(0.0),
+                    gid: attribute(object, "gid").ok(),
+                })
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+        TiledMap::from_layers(
+            attribute(map, "width")?,
+            attribute(map, "height")?,
+            (attribute(map, "tilewidth")?, attribute(map, "tileheight")?),
+            &data,
+            &objects.iter().collect::<Vec<_>>(),
+        )
+    }
+
+    /// Tile holding the center of an object, the objects are positioned in pixels
+    fn object_tile(object: &TiledObject, tile_size: (f64, f64)) -> (f64, f64) {
+        let center_y = if object.gid.is_some() { object.y - object.height / 2.0 } else { object.y + object.height / 2.0 };
+        (((object.x + object.width / 2.0) / tile_size.0).floor(), (center_y / tile_size.1).floor())
+    }
+
+    fn from_layers(
+        width: usize,
+        height: usize,
+        tile_size: (f64, f64),
+        data: &[u32],
+        objects: &[&TiledObject],
+    ) -> Result<TiledMap, Error> {
+        // the objects are placed by dividing their position by the tile size
+        if !(tile_size.0 > 0.0 && tile_size.1 > 0.0) {
+            return Err(Error::WrongMap(format!("tile size {} x {}, expected positive ones", tile_size.0, tile_size.1)));
+        }
+        let tile_count = width
+            .checked_mul(height)
+            .ok_or(Error::WrongMap(format!("size {} x {} too large", width, height)))?;
+        if data.len() != tile_count {
+            return Err(Error::WrongMap(format!("{} tiles in the collision layer, expected {}", data.len(), tile_count)));
+        }
+        let mut tiled_map = TiledMap {
+            width,
+            height,
+            walls: data.iter().map(|&tile| tile != 0).collect(),
+            starts: vec![],
+            exits: vec![],
+        };
+        for object in objects {
+            // the marker is given by the name, the type or the class of the object
+            let marker = [&object.name, &object.object_type, &object.class]
+                .into_iter()
+                .find(|marker| marker.eq_ignore_ascii_case(TiledMap::START) || marker.eq_ignore_ascii_case(TiledMap::EXIT));
+            let Some(marker) = marker else {
+                continue;
+            };
+            let (x, y) = TiledMap::object_tile(object, tile_size);
+            let tile = (x as usize, y as usize);
+            if x < 0.0 || y < 0.0 || tile.0 >= width || tile.1 >= height || tiled_map.walls[tile.1 * width + tile.0] {
+                return Err(Error::MarkerOnBlockedCase { name: marker.to_ascii_lowercase(), x: tile.0, y: tile.1 });
+            }
+            if marker.eq_ignore_ascii_case(TiledMap::START) {
+                tiled_map.starts.push(tile);
+            } else {
+                tiled_map.exits.push(tile);
+            }
+        }
+        Ok(tiled_map)
+    }
+
+    /// Grid of the map, one tile per case and the top row of the map as the top row of the grid
+    pub fn grid_info(&self) -> Result<GridInfo, Error> {
+        let mut grid_row = String::new();
+        for y in 0..self.height {
+            for x in 0..self.width {
+                grid_row.push(if self.starts.contains(&(x, y)) {
+                    GridInfo::CASE_START
+                } else if self.exits.contains(&(x, y)) {
+                    GridInfo::CASE_WIN
+                } else if self.walls[y * self.width + x] {
+                    GridInfo::CASE_CLOSE
+                } else {
+                    GridInfo::CASE_OPEN
+                });
+            }
+            grid_row.push('\n');
+        }
+        // a map without header, whose line and column are those of the tile
+        GridInfo::new(&grid_row).map_err(Error::Grid)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid_info::grid::ErrorKind;
+    use std::collections::BTreeSet;
+
+    fn fixture(file_name: &str) -> String {
+        std::fs::read_to_string(format!("{}/data/tiled/{}", env!("CARGO_MANIFEST_DIR"), file_name)).unwrap()
+    }
+
+    #[test]
+    fn test_json_and_tmx_fixtures_should_give_the_same_grid() {
+        let tiled_map = TiledMap::new(&fixture("dungeon.tmj"), TiledMap::COLLISION_LAYER).unwrap();
+        assert_eq!(tiled_map, TiledMap::new(&fixture("dungeon.tmx"), TiledMap::COLLISION_LAYER).unwrap());
+        assert_eq!((8, 6), (tiled_map.width, tiled_map.height));
+        assert_eq!(vec![(1, 1), (2, 4)], tiled_map.starts);
+        assert_eq!(vec![(6, 4)], tiled_map.exits);
+        let grid_info = tiled_map.grid_info().unwrap();
+        assert_eq!(vec![(4, 1), (1, 2)], grid_info.start_positions);
+        assert_eq!(BTreeSet::from([(1, 6)]), grid_info.exit_positions);
+        assert_eq!(vec!['E', 'O', 'O', 'O', 'E', 'O', 'X', 'E'], grid_info.grid[1]);
+    }
+    #[test]
+    fn test_should_read_tmx_tiles_written_in_xml_and_objects_by_class() {
+        let tmx = r#"<?xml version="1.0"?>
+<map width="3" height="1" tilewidth="16" tileheight="16">
+ <layer name="walls" width="3" height="1"><data><tile gid="0"/><tile gid="7"/><tile/></data></layer>
+ <objectgroup name="markers">
+  <object id="1" class="Start" x="0" y="16" width="16" height="16" gid="12"/>
+  <object id="2" name="door" type="exit" x="40" y="8"/>
+  <object id="3" name="chest" x="16" y="0"/>
+ </objectgroup>
+</map>"#;
+        let tiled_map = TiledMap::new(tmx, "walls").unwrap();
+        assert_eq!(vec![false, true, false], tiled_map.walls);
+        assert_eq!((vec![(0, 0)], vec![(2, 0)]), (tiled_map.starts, tiled_map.exits));
+    }
+    #[test]
+    fn test_should_return_errors_for_wrong_maps() {
+        assert_eq!(Err(Error::MissingLayer("walls".to_string())), TiledMap::new(&fixture("dungeon.tmj"), "walls"));
+        let base64_layer = r#"{"width": 1, "height": 1, "tilewidth": 8, "tileheight": 8,
+            "layers": [{"type": "tilelayer", "name": "collision", "data": "AAAAAA==", "encoding": "base64"}]}"#;
+        assert!(matches!(TiledMap::new(base64_layer, "collision"), Err(Error::UnsupportedLayer(_))));
+        assert!(matches!(TiledMap::new("{\"width\": 1}", "collision"), Err(Error::WrongMap(_))));
+        assert!(matches!(TiledMap::new("<map width=\"1\">", "collision"), Err(Error::WrongMap(_))));
+        let huge_layer = r#"{"width": 18446744073709551615, "height": 2, "tilewidth": 8, "tileheight": 8,
+            "layers": [{"type": "tilelayer", "name": "collision", "data": [0, 0]}]}"#;
+        assert!(matches!(TiledMap::new(huge_layer, "collision"), Err(Error::WrongMap(_))));
+        let flat_tiles = r#"{"width": 1, "height": 1, "tilewidth": 8, "tileheight": 0, "layers": [
+            {"type": "tilelayer", "name": "collision", "data": [0]},
+            {"type": "objectgroup", "name": "markers", "objects": [{"name": "start", "x": 4, "y": 4}]}]}"#;
+        assert!(matches!(TiledMap::new(flat_tiles, "collision"), Err(Error::WrongMap(_))));
+        let flat_tiles = "<map width=\"1\" height=\"1\" tilewidth=\"0\" tileheight=\"8\"><layer name=\"collision\"><data encoding=\"csv\">0</data></layer></map>";
+        assert!(matches!(TiledMap::new(flat_tiles, "collision"), Err(Error::WrongMap(_))));
+        let start_on_wall = r#"{"width": 2, "height": 1, "tilewidth": 8, "tileheight": 8, "layers": [
+            {"type": "tilelayer", "name": "collision", "data": [0, 3]},
+            {"type": "objectgroup", "name": "markers", "objects": [{"name": "start", "x": 12, "y": 4}]}]}"#;
+        assert_eq!(
+            Err(Error::MarkerOnBlockedCase { name: "start".to_string(), x: 1, y: 0 }),
+            TiledMap::new(start_on_wall, "collision")
+        );
+        let without_exit = r#"{"width": 2, "height": 1, "tilewidth": 8, "tileheight": 8, "layers": [
+            {"type": "group", "name": "level", "layers": [{"type": "tilelayer", "name": "collision", "data": [0, 3]}]},
+            {"type": "objectgroup", "name": "markers", "objects": [{"name": "start", "x": 4, "y": 4}]}]}"#;
+        match TiledMap::new(without_exit, "collision").unwrap().grid_info().err() {
+            Some(Error::Grid(grid_error)) => assert_eq!(ErrorKind::MisingExit, grid_error.kind),
+            other => panic!("Expected a grid error, but got {:?}", other),
+        }
+    }
+}
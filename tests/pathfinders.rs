@@ -0,0 +1,166 @@
+//! Every algorithm of `SearchMode::NAMES` must find paths of the optimal length,
+//! and the any-angle ones paths at most as long as the grid ones
+
+use std::collections::BTreeSet;
+use wazelentin::theta_star;
+use wazelentin::{Connectivity, GridInfo, Heuristic, Metric, SearchGrid, SearchMode};
+
+/// Maps of `data/` without weighted cases, on which a BFS path is the shortest
+fn unweighted_data_maps() -> Vec<(String, GridInfo)> {
+    let mut maps: Vec<(String, GridInfo)> = std::fs::read_dir(format!("{}/data", env!("CARGO_MANIFEST_DIR")))
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.is_file())
+        .map(|path| {
+            let grid_info = GridInfo::new(&std::fs::read_to_string(&path).unwrap()).unwrap();
+            (path.file_name().unwrap().to_string_lossy().to_string(), grid_info)
+        })
+        .filter(|(_, grid_info)| grid_info.grid.iter().flatten().all(|&case| GridInfo::case_cost(case).is_none_or(|cost| cost == 1)))
+        .collect();
+    maps.sort_by(|map, other_map| map.0.cmp(&other_map.0));
+    maps
+}
+
+/// Maps of 12 x 16 cases with a wall on about a third of them, 3 starts and 2 exits, always the same ones
+fn random_maps() -> Vec<GridInfo> {
+    let mut seed: u64 = 0x5eed;
+    let mut random = move |bound: u64| {
+        // linear congruential generator of Knuth's MMIX
+        seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+        (seed >> 33) % bound
+    };
+    (0..50)
+        .map(|_| {
+            let mut rows: Vec<Vec<char>> = (0..12)
+                .map(|_| (0..16).map(|_| if random(3) == 0 { GridInfo::CASE_CLOSE } else { GridInfo::CASE_OPEN }).collect())
+                .collect();
+            for case in [GridInfo::CASE_START, GridInfo::CASE_START, GridInfo::CASE_START, GridInfo::CASE_WIN, GridInfo::CASE_WIN] {
+                rows[random(12) as usize][random(16) as usize] = case;
+            }
+            let map: Vec<String> = rows.iter().map(|row| row.iter().collect()).collect();
+            GridInfo::new(&map.join("\n")).unwrap()
+        })
+        .filter(|grid_info| !grid_info.start_positions.is_empty() && !grid_info.exit_positions.is_empty())
+        .collect()
+}
+
+/// Names of the algorithms whose paths go from a case to a connected one
+fn grid_pathfinder_names() -> impl Iterator<Item = &'static str> {
+    SearchMode::NAMES
+        .into_iter()
+        .filter(|name| !SearchMode::from_name(name, Heuristic::Chebyshev).unwrap().pathfinder().is_any_angle())
+}
+
+/// Length of the path of every start, None when no exit can be reached
+fn path_lengths(grid_info: &GridInfo, search_mode: SearchMode, connectivity: Connectivity) -> Vec<Option<f64>> {
+    path_lengths_with_metric(grid_info, search_mode, connectivity, Metric::Chebyshev)
+}
+
+fn path_lengths_with_metric(grid_info: &GridInfo, search_mode: SearchMode, connectivity: Connectivity, metric: Metric) -> Vec<Option<f64>> {
+    let search_grid = SearchGrid::new(grid_info, metric, connectivity);
+    let pathfinder = search_mode.pathfinder();
+    grid_info
+        .start_positions
+        .iter()
+        .map(|&start_pos| {
+            pathfinder
+                .find_path(&search_grid, start_pos, &grid_info.exit_positions)
+                // rounded so that sums of √2 in another order are equal
+                .map(|path_result| (path_result.length * 1e9).round() / 1e9)
+        })
+        .collect()
+}
+
+#[test]
+fn test_every_pathfinder_should_have_the_name_of_its_search_mode() {
+    for name in SearchMode::NAMES {
+        assert_eq!(name, SearchMode::from_name(name, Heuristic::Chebyshev).unwrap().pathfinder().name());
+    }
+    assert!(SearchMode::from_name("teleport", Heuristic::Chebyshev).is_none());
+}
+
+#[test]
+fn test_every_pathfinder_should_find_the_bfs_length() {
+    let maps = unweighted_data_maps();
+    assert!(maps.len() > 5);
+    for (map_name, grid_info) in &maps {
+        for connectivity in [Connectivity::Moore, Connectivity::MooreNoCornerCutting, Connectivity::VonNeumann] {
+            let bfs_lengths = path_lengths(grid_info, SearchMode::Bfs, connectivity);
+            for name in grid_pathfinder_names() {
+                let search_mode = SearchMode::from_name(name, Heuristic::Chebyshev).unwrap();
+                assert_eq!(bfs_lengths, path_lengths(grid_info, search_mode, connectivity), "{} {} {:?}", map_name, name, connectivity);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_every_pathfinder_should_return_none_without_reachable_exit() {
+    let grid_info = GridInfo::new("SEX").unwrap();
+    let search_grid = SearchGrid::new(&grid_info, Metric::Octile, Connectivity::Moore);
+    for name in SearchMode::NAMES {
+        let pathfinder = SearchMode::from_name(name, Heuristic::Octile).unwrap().pathfinder();
+        assert!(pathfinder.find_path(&search_grid, (0, 0), &BTreeSet::from([(0, 2)])).is_none(), "{}", name);
+        assert!(pathfinder.find_path(&search_grid, (0, 0), &grid_info.exit_positions).is_none(), "{}", name);
+    }
+}
+
+#[test]
+fn test_every_pathfinder_should_return_the_start_alone_when_it_is_an_exit() {
+    let grid_info = GridInfo::new("OOOX\nSOOO\nOOOO").unwrap();
+    for connectivity in [Connectivity::Moore, Connectivity::MooreNoCornerCutting, Connectivity::VonNeumann] {
+        let search_grid = SearchGrid::new(&grid_info, Metric::Octile, connectivity);
+        for name in SearchMode::NAMES {
+            let pathfinder = SearchMode::from_name(name, Heuristic::Octile).unwrap().pathfinder();
+            let path_result = pathfinder.find_path(&search_grid, (1, 0), &BTreeSet::from([(1, 0), (2, 3)])).unwrap();
+            assert_eq!((vec![(1, 0)], (1, 0)), (path_result.path, path_result.exit_pos), "{} {:?}", name, connectivity);
+            assert_eq!((0.0, 0.0), (path_result.length, path_result.cost), "{} {:?}", name, connectivity);
+        }
+    }
+}
+
+#[test]
+fn test_every_pathfinder_should_find_the_shortest_length_on_random_maps() {
+    let maps = random_maps();
+    assert!(maps.len() > 40);
+    for (map_index, grid_info) in maps.iter().enumerate() {
+        let bfs_lengths = path_lengths(grid_info, SearchMode::Bfs, Connectivity::Moore);
+        // with √2 diagonal moves the paths with the fewest moves are no longer the shortest, Dijkstra ones are
+        let dijkstra_lengths = path_lengths_with_metric(grid_info, SearchMode::Dijkstra, Connectivity::Moore, Metric::Octile);
+        for name in grid_pathfinder_names() {
+            let search_mode = SearchMode::from_name(name, Heuristic::Chebyshev).unwrap();
+            assert_eq!(bfs_lengths, path_lengths(grid_info, search_mode, Connectivity::Moore), "map {} {}\n{}", map_index, name, grid_info);
+            // the heuristic must not overestimate the √2 moves
+            let search_mode = SearchMode::from_name(name, Heuristic::Octile).unwrap();
+            if search_mode.pathfinder().follows_costs() {
+                let lengths = path_lengths_with_metric(grid_info, search_mode, Connectivity::Moore, Metric::Octile);
+                assert_eq!(dijkstra_lengths, lengths, "map {} {}\n{}", map_index, name, grid_info);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_any_angle_pathfinders_should_find_paths_in_line_of_sight_no_longer_than_the_grid_ones() {
+    let any_angle_names: Vec<&str> = SearchMode::NAMES.into_iter().filter(|name| !grid_pathfinder_names().any(|grid_name| grid_name == *name)).collect();
+    assert!(!any_angle_names.is_empty());
+    for (map_index, grid_info) in random_maps().iter().enumerate() {
+        // a line of sight never cuts a corner
+        let search_grid = SearchGrid::new(grid_info, Metric::Octile, Connectivity::MooreNoCornerCutting);
+        let dijkstra_lengths = path_lengths_with_metric(grid_info, SearchMode::Dijkstra, Connectivity::MooreNoCornerCutting, Metric::Octile);
+        for name in &any_angle_names {
+            let pathfinder = SearchMode::from_name(name, Heuristic::Octile).unwrap().pathfinder();
+            for (&start_pos, dijkstra_length) in grid_info.start_positions.iter().zip(&dijkstra_lengths) {
+                let path_result = pathfinder.find_path(&search_grid, start_pos, &grid_info.exit_positions);
+                assert_eq!(dijkstra_length.is_some(), path_result.is_some(), "map {} {}\n{}", map_index, name, grid_info);
+                let Some(path_result) = path_result else {
+                    continue;
+                };
+                assert_eq!(start_pos, path_result.path[0]);
+                assert!(grid_info.exit_positions.contains(&path_result.exit_pos));
+                assert!(path_result.path.windows(2).all(|segment| theta_star::line_of_sight(&search_grid, segment[0], segment[1])));
+                assert!(path_result.length <= dijkstra_length.unwrap() + 1e-6, "map {} {} {:?}\n{}", map_index, name, start_pos, grid_info);
+            }
+        }
+    }
+}
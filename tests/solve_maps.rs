@@ -0,0 +1,73 @@
+//! Maps of `data/` read and solved through the public API of the library
+
+use wazelentin::tiled_map::TiledMap;
+use wazelentin::{GridInfo, Metric, PathDetective, SearchMode};
+
+fn read_data_map(map_name: &str) -> GridInfo {
+    let grid_row_file = std::fs::read_to_string(format!("{}/data/{}", env!("CARGO_MANIFEST_DIR"), map_name)).unwrap();
+    GridInfo::new(&grid_row_file).unwrap()
+}
+
+#[test]
+fn test_should_solve_a_map_file() {
+    let path_result = PathDetective::new(read_data_map("map_1"), None).find_and_transmit_path().unwrap();
+    assert_eq!(vec![(0, 2), (1, 2), (2, 2), (3, 3), (4, 4), (3, 5), (4, 6)], path_result.path);
+    assert_eq!((4, 6), path_result.exit_pos);
+    assert_eq!(6.0, path_result.length);
+}
+
+#[test]
+fn test_tiled_json_and_tmx_maps_should_give_the_same_paths() {
+    let grid_infos: Vec<GridInfo> = ["dungeon.tmj", "dungeon.tmx"]
+        .iter()
+        .map(|file_name| {
+            let map_file = std::fs::read_to_string(format!("{}/data/tiled/{}", env!("CARGO_MANIFEST_DIR"), file_name)).unwrap();
+            TiledMap::new(&map_file, TiledMap::COLLISION_LAYER).unwrap().grid_info().unwrap()
+        })
+        .collect();
+    assert_eq!(grid_infos[0].to_map_string(), grid_infos[1].to_map_string());
+    let mut wazelentin = PathDetective::new(grid_infos[0].clone(), None);
+    wazelentin.metric = Metric::Octile;
+    wazelentin.search_mode = SearchMode::Dijkstra;
+    assert!(wazelentin.find_and_transmit_paths_from_every_start().iter().all(Option::is_some));
+}
+
+#[test]
+fn test_should_solve_every_map_of_a_stream() {
+    let stream = format!("{}---\n{}", GridInfo::new("SOX").unwrap(), read_data_map("map_2"));
+    let maps = GridInfo::split_maps(&stream);
+    assert_eq!(2, maps.len());
+    for (_, map) in maps {
+        let grid_info = GridInfo::new(&map).unwrap();
+        assert!(PathDetective::new(grid_info, None).find_and_transmit_path().is_some());
+    }
+}
+
+#[test]
+fn test_every_map_of_a_stream_should_have_a_result_even_a_wrong_one() {
+    use std::io::Write;
+    use std::process::{Command, Stdio};
+    let run = |format: &str| {
+        let mut child = Command::new(env!("CARGO_BIN_EXE_wazelentin"))
+            .args(["-g", "-", "--no-display", "--format", format])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .unwrap();
+        // the second map has an unknown case
+        child.stdin.take().unwrap().write_all(b"SOX\n---\nSOQ\n---\nXOS\n").unwrap();
+        let output = child.wait_with_output().unwrap();
+        assert!(!output.status.success());
+        String::from_utf8(output.stdout).unwrap()
+    };
+    let text_lines: Vec<String> = run("text").lines().map(String::from).collect();
+    assert_eq!(5, text_lines.len(), "{:?}", text_lines);
+    assert_eq!((GridInfo::MAP_DELIMITER, GridInfo::MAP_DELIMITER), (text_lines[1].as_str(), text_lines[3].as_str()));
+    assert!(text_lines[2].starts_with("<stdin> (map 2): line 3, column 3"), "{}", text_lines[2]);
+    let json_lines: Vec<serde_json::Value> = run("json").lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+    assert_eq!(3, json_lines.len());
+    assert_eq!("<stdin> (map 2)", json_lines[1]["grid_file"]);
+    assert!(json_lines[1]["error"].is_string());
+    assert_eq!(serde_json::json!([0, 2]), json_lines[2]["paths"][0]["start"]);
+}